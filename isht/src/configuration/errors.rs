@@ -4,6 +4,8 @@ use crate::ConfigStatment;
 pub enum ConfigurationError {
     NotRecognizedGroup(String),
     NotRecognizedKeybindMode(String),
+    NotRecognizedLanguageBinding(String),
+    NotRecognizedSetting(String),
     ExpectedTasksBlock,
     InvalidStatment(ConfigStatment),
 }
@@ -24,6 +26,13 @@ impl std::fmt::Display for ConfigurationError {
                 f,
                 "Ishtar does not recognize a keyboard mode named as '{mode}"
             ),
+            Self::NotRecognizedLanguageBinding(name) => write!(
+                f,
+                "Ishtar does not recognize '{name}' as a way of binding languages"
+            ),
+            Self::NotRecognizedSetting(name) => {
+                write!(f, "Ishtar does not recognize a setting named as '{name}'")
+            }
         }
     }
 }
@@ -6,10 +6,93 @@ use std::collections::HashMap;
 
 type Keybinds = HashMap<String, Vec<ConfigStatment>>;
 
+///Languages bound to file extensions, file names and shebang interpreters, all mapping to the
+///name of the language
+#[derive(Debug, Default)]
+pub struct LanguageBindings {
+    pub extensions: HashMap<String, String>,
+    pub file_names: HashMap<String, String>,
+    pub shebangs: HashMap<String, String>,
+}
+
+///Settings of a filetype, used instead of the general ones when set
+#[derive(Debug, Default)]
+pub struct FileTypeSettings {
+    pub wrap: Option<bool>,
+    pub wrap_column: Option<usize>,
+    pub indent_width: Option<usize>,
+    pub expandtab: Option<bool>,
+    pub tab_width: Option<usize>,
+    pub auto_pairs: Option<bool>,
+}
+
+///Settings changing how the editor behaves
+#[derive(Debug)]
+pub struct EditorSettings {
+    pub scrolloff: usize,            //lines kept visible above and below the cursor
+    pub side_scrolloff: usize,       //columns kept visible on the left and right of the cursor
+    pub wrap: bool,                  //soft wraps lines too long to be shown
+    pub wrap_column: usize,          //column lines are wrapped at, 0 for the width of the area
+    pub fallback_encoding: String,   //files are read with it when they are not UTF-8 or UTF-16
+    pub large_file_threshold: usize, //MiB from which files are not loaded, 0 to load them all
+    pub indent_width: usize,         //columns of a level of indentation
+    pub expandtab: bool,             //indents with spaces instead of a tab per level
+    pub tab_width: usize,            //columns between tab stops
+    pub auto_pairs: bool,            //typing a bracket or a quote also writes its closer
+    pub clipboard_history: usize,    //last copied and deleted texts kept to be pasted again
+    pub filetypes: HashMap<String, FileTypeSettings>, //by language name or file extension
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            scrolloff: 3,
+            side_scrolloff: 5,
+            wrap: false,
+            wrap_column: 0,
+            fallback_encoding: "windows-1252".to_string(),
+            large_file_threshold: 64,
+            indent_width: 4,
+            expandtab: true,
+            tab_width: 4,
+            auto_pairs: true,
+            clipboard_history: 20,
+            filetypes: HashMap::new(),
+        }
+    }
+}
+
+///Gets the number of a setting statment
+fn num_setting(rhs: &ConfigStatment) -> Result<usize> {
+    match rhs {
+        ConfigStatment::Num(n) => Ok(*n as usize),
+        _ => Err(ConfigurationError::InvalidStatment(rhs.clone()).into()),
+    }
+}
+
+///Gets the text of a setting statment, written without quotes
+fn text_setting(rhs: &ConfigStatment) -> Result<String> {
+    match rhs {
+        ConfigStatment::Cmd(text) => Ok(text.clone()),
+        _ => Err(ConfigurationError::InvalidStatment(rhs.clone()).into()),
+    }
+}
+
+///Gets the boolean of a setting statment, written as `true` or `false`
+fn bool_setting(rhs: &ConfigStatment) -> Result<bool> {
+    match rhs {
+        ConfigStatment::Cmd(b) if b == "true" => Ok(true),
+        ConfigStatment::Cmd(b) if b == "false" => Ok(false),
+        _ => Err(ConfigurationError::InvalidStatment(rhs.clone()).into()),
+    }
+}
+
 #[derive(Debug)]
 pub struct IshtarConfiguration {
     pub keybinds: [Keybinds; 3], //normal, modify, selection
     pub colors: HashMap<String, u32>,
+    pub languages: LanguageBindings,
+    pub settings: EditorSettings,
 }
 
 impl IshtarConfiguration {
@@ -42,6 +125,128 @@ impl IshtarConfiguration {
         }
         Ok(())
     }
+    ///Generates data into the target based on the languages group, whose subgroups are
+    ///`extensions`, `files` and `shebangs`. Each statment is written as `target = language`
+    pub fn generate_from_languages_group(
+        data: &ConfigStatment,
+        target: &mut LanguageBindings,
+    ) -> Result<()> {
+        let ConfigStatment::Block(contents) = data else {
+            unreachable!();
+        };
+        for content in contents {
+            let ConfigStatment::SubGroup { name, data } = content else {
+                unreachable!();
+            };
+            let bindings = match name.as_ref() {
+                "extensions" => &mut target.extensions,
+                "files" => &mut target.file_names,
+                "shebangs" => &mut target.shebangs,
+                _ => {
+                    return Err(
+                        ConfigurationError::NotRecognizedLanguageBinding(name.clone()).into(),
+                    )
+                }
+            };
+            let ConfigStatment::Block(data) = &**data else {
+                return Err(ConfigurationError::ExpectedTasksBlock.into());
+            };
+            for data in data.iter() {
+                match data {
+                    ConfigStatment::CmdDecl { lhs, rhs } => {
+                        let ConfigStatment::Cmd(language) = &**rhs else {
+                            return Err(ConfigurationError::InvalidStatment((**rhs).clone()).into());
+                        };
+                        bindings.insert(lhs.clone(), language.clone());
+                    }
+                    e => return Err(ConfigurationError::InvalidStatment(e.clone()).into()),
+                }
+            }
+        }
+        Ok(())
+    }
+    ///Generates data into the target based on the settings group. Each statment is written as
+    ///`setting = value` inside the subgroup of the setting, like `scrolloff = 5` inside `viewport`
+    pub fn generate_from_settings_group(
+        data: &ConfigStatment,
+        target: &mut EditorSettings,
+    ) -> Result<()> {
+        let ConfigStatment::Block(contents) = data else {
+            unreachable!();
+        };
+        for content in contents {
+            let ConfigStatment::SubGroup { name, data } = content else {
+                unreachable!();
+            };
+            let ConfigStatment::Block(data) = &**data else {
+                return Err(ConfigurationError::ExpectedTasksBlock.into());
+            };
+            for data in data.iter() {
+                let ConfigStatment::CmdDecl { lhs, rhs } = data else {
+                    return Err(ConfigurationError::InvalidStatment(data.clone()).into());
+                };
+                match (name.as_ref(), lhs.as_ref()) {
+                    ("viewport", "scrolloff") => target.scrolloff = num_setting(rhs)?,
+                    ("viewport", "side_scrolloff") => target.side_scrolloff = num_setting(rhs)?,
+                    ("wrap", "enabled") => target.wrap = bool_setting(rhs)?,
+                    ("wrap", "column") => target.wrap_column = num_setting(rhs)?,
+                    ("encoding", "fallback") => target.fallback_encoding = text_setting(rhs)?,
+                    ("large_file", "threshold") => target.large_file_threshold = num_setting(rhs)?,
+                    ("indent", "width") => target.indent_width = num_setting(rhs)?,
+                    ("indent", "expandtab") => target.expandtab = bool_setting(rhs)?,
+                    ("indent", "tab_width") => target.tab_width = num_setting(rhs)?,
+                    ("brackets", "auto_pairs") => target.auto_pairs = bool_setting(rhs)?,
+                    ("clipboard", "history") => target.clipboard_history = num_setting(rhs)?,
+                    _ => {
+                        return Err(ConfigurationError::NotRecognizedSetting(format!(
+                            "{name}.{lhs}"
+                        ))
+                        .into())
+                    }
+                };
+            }
+        }
+        Ok(())
+    }
+    ///Generates data into the target based on the filetypes group, whose subgroups are named by
+    ///a language or a file extension and hold the settings used for those files
+    pub fn generate_from_filetypes_group(
+        data: &ConfigStatment,
+        target: &mut HashMap<String, FileTypeSettings>,
+    ) -> Result<()> {
+        let ConfigStatment::Block(contents) = data else {
+            unreachable!();
+        };
+        for content in contents {
+            let ConfigStatment::SubGroup { name, data } = content else {
+                unreachable!();
+            };
+            let ConfigStatment::Block(data) = &**data else {
+                return Err(ConfigurationError::ExpectedTasksBlock.into());
+            };
+            let filetype = target.entry(name.clone()).or_default();
+            for data in data.iter() {
+                let ConfigStatment::CmdDecl { lhs, rhs } = data else {
+                    return Err(ConfigurationError::InvalidStatment(data.clone()).into());
+                };
+                match lhs.as_ref() {
+                    "wrap" => filetype.wrap = Some(bool_setting(rhs)?),
+                    "wrap_column" => filetype.wrap_column = Some(num_setting(rhs)?),
+                    "indent_width" => filetype.indent_width = Some(num_setting(rhs)?),
+                    "expandtab" => filetype.expandtab = Some(bool_setting(rhs)?),
+                    "tab_width" => filetype.tab_width = Some(num_setting(rhs)?),
+                    "auto_pairs" => filetype.auto_pairs = Some(bool_setting(rhs)?),
+                    _ => {
+                        return Err(ConfigurationError::NotRecognizedSetting(format!(
+                            "{name}.{lhs}"
+                        ))
+                        .into())
+                    }
+                };
+            }
+        }
+        Ok(())
+    }
     ///Generates data into the target based on the keybind group
     pub fn generate_from_keybinds_group(
         data: &ConfigStatment,
@@ -93,10 +298,15 @@ impl IshtarConfiguration {
                 ConfigStatment::Group { name, data } => {
                     let nameref = name.as_ref();
                     match nameref {
-                        "keybinds" => {
-                            Self::generate_from_keybinds_group(&data, &mut this.keybinds)?
+                        "keybinds" => Self::generate_from_keybinds_group(data, &mut this.keybinds)?,
+                        "colors" => Self::generate_from_colors_group(data, &mut this.colors)?,
+                        "languages" => {
+                            Self::generate_from_languages_group(data, &mut this.languages)?
+                        }
+                        "settings" => Self::generate_from_settings_group(data, &mut this.settings)?,
+                        "filetypes" => {
+                            Self::generate_from_filetypes_group(data, &mut this.settings.filetypes)?
                         }
-                        "colors" => Self::generate_from_colors_group(&data, &mut this.colors)?,
                         _ => {
                             return Err(ConfigurationError::NotRecognizedGroup(name.clone()).into())
                         }
@@ -111,6 +321,8 @@ impl IshtarConfiguration {
         Self {
             keybinds: [HashMap::new(), HashMap::new(), HashMap::new()],
             colors: HashMap::new(),
+            languages: LanguageBindings::default(),
+            settings: EditorSettings::default(),
         }
     }
 }
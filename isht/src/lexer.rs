@@ -37,22 +37,21 @@ pub fn lex_content(content: String) -> VecDeque<ConfigToken> {
                 }
                 _ if c.is_ascii_digit() => {
                     let mut buf = String::new();
-                    let mut flag = false;
                     while let Some(c) = chars.get(idx) {
                         if c.is_ascii_hexdigit() || *c == 'x' {
                             buf.push(*c);
                             idx += 1;
-                            if !c.is_ascii_digit() || *c == 'x' {
-                                flag = true;
-                            }
                         } else {
                             break;
                         }
                     }
                     idx -= 1;
-                    tokens.push_back(ConfigToken::Num(
-                        u32::from_str_radix(&buf[2..], if flag { 16 } else { 10 }).unwrap(),
-                    ))
+                    //Hex numbers are written as 0xRRGGBB, the rest are decimal
+                    let num = match buf.strip_prefix("0x") {
+                        Some(hex) => u32::from_str_radix(hex, 16),
+                        None => buf.parse(),
+                    };
+                    tokens.push_back(ConfigToken::Num(num.unwrap()))
                 }
                 '-' => {
                     idx += 1;
@@ -12,6 +12,7 @@ pub enum IshtParseError {
     ExpectingEq,
     BlockExpected(ConfigToken),
     InvalidHex(ParseIntError),
+    InvalidRegister(String),
     ReachedEOF,
 }
 impl std::error::Error for IshtParseError {}
@@ -33,6 +34,10 @@ impl std::fmt::Display for IshtParseError {
                 f,
                 "Invalid Hex value found:\n{e}\nFollow the pattern: 0xRRGGBB"
             ),
+            Self::InvalidRegister(r) => write!(
+                f,
+                "Invalid register '{r}'\nFollow the pattern: <a-z> [times]"
+            ),
             Self::BlockExpected(tk) => write!(f, "Expected a block statment, instead got {tk:?}"),
             Self::ReachedEOF => write!(f, "Reached EOF"),
         }
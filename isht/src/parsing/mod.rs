@@ -9,6 +9,29 @@ use crate::{
 use anyhow::Result;
 use errors::IshtParseError;
 use statment::ConfigStatment;
+///Reads a register written as a lowercase letter
+fn parse_register(content: &str) -> Result<char> {
+    let mut chars = content.chars();
+    match (chars.next(), chars.next()) {
+        (Some(name), None) if name.is_ascii_lowercase() => Ok(name),
+        _ => Err(IshtParseError::InvalidRegister(content.to_string()).into()),
+    }
+}
+///Reads the register of a macro to play, followed by how many times to play it when given as
+///"a 3"
+fn parse_play(content: &str) -> Result<(char, u32)> {
+    let invalid = || IshtParseError::InvalidRegister(content.to_string());
+    let mut parts = content.split_whitespace();
+    let name = parse_register(parts.next().ok_or_else(invalid)?)?;
+    let times = match parts.next() {
+        Some(n) => n.parse().map_err(|_| invalid())?,
+        None => 1,
+    };
+    if parts.next().is_some() {
+        return Err(invalid().into());
+    }
+    Ok((name, times))
+}
 fn parse_block(tokens: &mut VecDeque<ConfigToken>) -> Result<ConfigStatment> {
     let mut block = Vec::new();
     loop {
@@ -19,7 +42,9 @@ fn parse_block(tokens: &mut VecDeque<ConfigToken>) -> Result<ConfigStatment> {
         match tokens.pop_front() {
             Some(ConfigToken::Identifier(s)) => {
                 let task = match s.as_ref() {
-                    "ExecCmd" | "ExecPrompt" | "Write" | "Color" => {
+                    "ExecCmd" | "ExecPrompt" | "Write" | "Search" | "Replace" | "Color"
+                    | "RecordMacro" | "PlayMacro" | "PressKey" | "CopyToRegister"
+                    | "AppendToRegister" | "PasteRegister" => {
                         if !matches!(tokens.pop_front(), Some(ConfigToken::Arrow)) {
                             return Err(IshtParseError::ExpectingArrow(s).into());
                         };
@@ -30,6 +55,19 @@ fn parse_block(tokens: &mut VecDeque<ConfigToken>) -> Result<ConfigStatment> {
                             "ExecCmd" => CmdTask::ExecCmd(content),
                             "ExecPrompt" => CmdTask::ExecutePrompt(content),
                             "Write" => CmdTask::Write(content),
+                            "Search" => CmdTask::Search(content),
+                            "Replace" => CmdTask::Replace(content),
+                            "RecordMacro" => CmdTask::RecordMacro(parse_register(&content)?),
+                            "PlayMacro" => {
+                                let (name, times) = parse_play(&content)?;
+                                CmdTask::PlayMacro(name, times)
+                            }
+                            "PressKey" => CmdTask::PressKey(content),
+                            "CopyToRegister" => CmdTask::CopyToRegister(parse_register(&content)?),
+                            "AppendToRegister" => {
+                                CmdTask::AppendToRegister(parse_register(&content)?)
+                            }
+                            "PasteRegister" => CmdTask::PasteRegister(parse_register(&content)?),
                             "Color" => {
                                 return Ok(ConfigStatment::Color(
                                     u32::from_str_radix(&content, 16)
@@ -40,7 +78,7 @@ fn parse_block(tokens: &mut VecDeque<ConfigToken>) -> Result<ConfigStatment> {
                         };
                         ConfigStatment::Task(task)
                     }
-                    "MoveToLine" | "MoveToRow" => {
+                    "MoveToLine" | "MoveToRow" | "PasteHistory" => {
                         if !matches!(tokens.pop_front(), Some(ConfigToken::Arrow)) {
                             return Err(IshtParseError::ExpectingArrow(s).into());
                         };
@@ -50,6 +88,7 @@ fn parse_block(tokens: &mut VecDeque<ConfigToken>) -> Result<ConfigStatment> {
                         let task = match s.as_ref() {
                             "MoveToLine" => CmdTask::MoveToLine(n),
                             "MoveToRow" => CmdTask::MoveToRow(n),
+                            "PasteHistory" => CmdTask::PasteHistory(n),
                             _ => unreachable!(),
                         };
                         ConfigStatment::Task(task)
@@ -97,7 +136,21 @@ fn parse_subgroup(
             };
             if matches!(
                 tk.as_ref(),
-                "ExecCmd" | "ExecPrompt" | "MoveToLine" | "MoveToRow" | "Write" | "Color"
+                "ExecCmd"
+                    | "ExecPrompt"
+                    | "MoveToLine"
+                    | "MoveToRow"
+                    | "Write"
+                    | "Search"
+                    | "Replace"
+                    | "Color"
+                    | "RecordMacro"
+                    | "PlayMacro"
+                    | "PressKey"
+                    | "CopyToRegister"
+                    | "AppendToRegister"
+                    | "PasteRegister"
+                    | "PasteHistory"
             ) {
                 return Err(IshtParseError::WrongUseofReserved.into());
             }
@@ -115,8 +168,10 @@ fn parse_subgroup(
                             };
                             ConfigStatment::Color(num)
                         }
-                        "ExecCmd" | "ExecPrompt" | "Write" | "ModifyFile" | "CreateFile"
-                        | "DeleteFile" => {
+                        "ExecCmd" | "ExecPrompt" | "Write" | "Search" | "Replace"
+                        | "ModifyFile" | "CreateFile" | "DeleteFile" | "RecordMacro"
+                        | "PlayMacro" | "PressKey" | "CopyToRegister" | "AppendToRegister"
+                        | "PasteRegister" => {
                             if !matches!(tokens.pop_front(), Some(ConfigToken::Arrow)) {
                                 return Err(IshtParseError::ExpectingArrow(s).into());
                             };
@@ -127,14 +182,31 @@ fn parse_subgroup(
                                 "ExecCmd" => CmdTask::ExecCmd(content),
                                 "ExecPrompt" => CmdTask::ExecutePrompt(content),
                                 "Write" => CmdTask::Write(content),
+                                "Search" => CmdTask::Search(content),
+                                "Replace" => CmdTask::Replace(content),
                                 "ModifyFile" => CmdTask::ModifyFile(content),
                                 "CreateFile" => CmdTask::CreateFile(content),
                                 "DeleteFile" => CmdTask::DeleteFile(content),
+                                "RecordMacro" => CmdTask::RecordMacro(parse_register(&content)?),
+                                "PlayMacro" => {
+                                    let (name, times) = parse_play(&content)?;
+                                    CmdTask::PlayMacro(name, times)
+                                }
+                                "PressKey" => CmdTask::PressKey(content),
+                                "CopyToRegister" => {
+                                    CmdTask::CopyToRegister(parse_register(&content)?)
+                                }
+                                "AppendToRegister" => {
+                                    CmdTask::AppendToRegister(parse_register(&content)?)
+                                }
+                                "PasteRegister" => {
+                                    CmdTask::PasteRegister(parse_register(&content)?)
+                                }
                                 _ => unreachable!(),
                             };
                             ConfigStatment::Task(task)
                         }
-                        "MoveToLine" | "MoveToRow" => {
+                        "MoveToLine" | "MoveToRow" | "PasteHistory" => {
                             if !matches!(tokens.pop_front(), Some(ConfigToken::Arrow)) {
                                 return Err(IshtParseError::ExpectingArrow(s).into());
                             };
@@ -144,6 +216,7 @@ fn parse_subgroup(
                             let task = match s.as_ref() {
                                 "MoveToLine" => CmdTask::MoveToLine(n),
                                 "MoveToRow" => CmdTask::MoveToRow(n),
+                                "PasteHistory" => CmdTask::PasteHistory(n),
                                 _ => unreachable!(),
                             };
                             ConfigStatment::Task(task)
@@ -157,6 +230,7 @@ fn parse_subgroup(
                         }
                     },
                     Some(ConfigToken::Task(t)) => ConfigStatment::Task(t),
+                    Some(ConfigToken::Num(n)) => ConfigStatment::Num(n),
                     None => return Err(IshtParseError::ReachedEOF.into()),
                     t => return Err(IshtParseError::InvalidTokenPosition(t.unwrap()).into()),
                 };
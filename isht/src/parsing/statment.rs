@@ -17,5 +17,6 @@ pub enum ConfigStatment {
     Block(Vec<ConfigStatment>),
     Task(CmdTask),
     Color(u32),
+    Num(u32),
     Cmd(String),
 }
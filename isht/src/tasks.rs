@@ -16,6 +16,12 @@ pub enum CmdTask {
     CopyToEditor,
     PasteSys,
     PasteEditor,
+    CopyToRegister(char), //copies the selection into the given register, a to z
+    AppendToRegister(char), //appends the selection to the given register
+    PasteRegister(char),
+    ShowHistory, //opens the last copied and deleted texts to paste one of them
+    CloseHistory,
+    PasteHistory(u32), //pastes the given entry of the history, the newest being 0
     //fs
     CreateFile(String),         //File target
     ModifyFile(String),         //File target
@@ -23,6 +29,8 @@ pub enum CmdTask {
     DeleteFile(String),         //File target
     SaveFileAs(String),
     SaveFile,
+    ReopenWithEncoding(String), //reads the current file again in the given encoding
+    SetEncoding(String),        //saves the current file in the given encoding from now on
     WriteOnFile,
     CopyFile,      //Copies the file path to clipboard
     Write(String), //writes on the current buffer; SysClip and EditorClip are reserved words for
@@ -35,12 +43,14 @@ pub enum CmdTask {
     ReqDeleteFile,
     ReqModifyFile,
     ReqSaveFile,
+    ReqReplace, //asks for confirmation on each match of a replace
 
     StopSearch,
     //Cmd mode
     EnterNormal,
     EnterModify,
-    EnterSelection, //mode to select
+    EnterSelection,      //mode to select
+    EnterBlockSelection, //mode to select a rectangle of text
     //Cmds
     ExecCmd(String), //executes the following commands, its terminal ones.
     ExecutePrompt(String),
@@ -49,15 +59,55 @@ pub enum CmdTask {
     DeleteLine,
     CopySelection,
     DeleteSelection,
+    ExpandSelection, //grows the selection to the syntax node around it
+    ShrinkSelection, //goes back to the selection before the last expansion
+    Reindent,        //indents the selected lines again, or the current one
+    //Cursors
+    AddCursorAbove,
+    AddCursorBelow,
+    AddNextOccurrence, //selects the next occurrence of the selection with a new cursor
+    SplitSelection,    //one cursor per line of the selection
+    ClearCursors,      //keeps only the main cursor
     //Move
     MoveToLine(u32),
     MoveToRow(u32),
-    MoveIOW, //init of word
-    MoveEOW, //end of word
-    MoveEOL, //end of line
-    MoveIOL, //init of line
-    MoveEOB, //end of buffer
-    MoveIOB, // init of buffer
+    MoveIOW,      //init of word
+    MoveEOW,      //end of word
+    MoveEOL,      //end of line
+    MoveIOL,      //init of line
+    MoveEOB,      //end of buffer
+    MoveIOB,      // init of buffer
+    MatchBracket, //goes to the bracket paired with the one under the cursor
+    //Scroll, the cursor only moves when it would leave the view
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+    ToggleWrap,        //soft wraps the lines of the current buffer or stops wrapping them
+    ToggleLineEnding,  //saves the current buffer with CRLF line endings if it used LF and back
+    ToggleHex,         //shows the bytes of the current buffer as hex or back as text
+    GotoOffset(usize), //moves to the byte at the given offset of the current buffer
+    LoadWholeFile,     //reads the whole large file being shown so it can be edited
+    //History
+    Undo,
+    Redo,
+    //Search
+    Search(String), //searches the given query on the current buffer
+    SearchNext,
+    SearchPrev,
+    ClearSearch,
+    Replace(String),     //replace command written as %s/pattern/replacement/flags
+    ReplaceAnswer(char), //answer given to a replace asking for confirmation. By now not avaible in isht
+    //Folding
+    Fold,           //closes the fold around the cursor
+    Unfold,         //opens the fold on the cursor line
+    ToggleAllFolds, //opens every fold if one is closed, closes all of them otherwise
+    //Macros
+    RecordMacro(char), //records the keys pressed from now on into the given register
+    StopMacro,         //stops recording, keeping the keys in the register
+    PlayMacro(char, u32), //presses the keys of the given register the given times
+    SaveMacro(char, String), //binds the given register to the given keybind. By now not avaible in isht
+    PressKey(String),        //presses the given key, written as Control-s
 
     Swap, //swap file buffers
 
@@ -89,6 +139,8 @@ impl CmdTask {
             "CopyToEditor" => Self::CopyToEditor,
             "PasteSys" => Self::PasteSys,
             "PasteEditor" => Self::PasteEditor,
+            "ShowHistory" => Self::ShowHistory,
+            "CloseHistory" => Self::CloseHistory,
             "SaveFile" => Self::SaveFile,
             "WriteOnFile" => Self::WriteOnFile,
             "CopyFile" => Self::CopyFile,
@@ -96,11 +148,21 @@ impl CmdTask {
             "EnterCmd" | "EnterNormal" => Self::EnterNormal,
             "EnterModify" => Self::EnterModify,
             "EnterSelection" => Self::EnterSelection, //Mode to select
+            "EnterBlockSelection" => Self::EnterBlockSelection,
             //Selection
             "SelectLine" => Self::SelectLine,
             "DeleteLine" => Self::DeleteLine,
             "CopySelection" => Self::CopySelection,
             "DeleteSelection" => Self::DeleteSelection,
+            "ExpandSelection" => Self::ExpandSelection,
+            "ShrinkSelection" => Self::ShrinkSelection,
+            "Reindent" => Self::Reindent,
+            //Cursors
+            "AddCursorAbove" => Self::AddCursorAbove,
+            "AddCursorBelow" => Self::AddCursorBelow,
+            "AddNextOccurrence" => Self::AddNextOccurrence,
+            "SplitSelection" => Self::SplitSelection,
+            "ClearCursors" => Self::ClearCursors,
 
             "RequestSearchCurr" => Self::ReqSearchCurr, //Open file manager with this request on
             //the current directory
@@ -116,6 +178,29 @@ impl CmdTask {
             "MoveIOL" => Self::MoveIOL, //Init of line
             "MoveEOB" => Self::MoveEOB, //End of buffer
             "MoveIOB" => Self::MoveIOB, //Init of buffer
+            "MatchBracket" => Self::MatchBracket,
+            //Scroll
+            "ScrollUp" => Self::ScrollUp,
+            "ScrollDown" => Self::ScrollDown,
+            "ScrollLeft" => Self::ScrollLeft,
+            "ScrollRight" => Self::ScrollRight,
+            "ToggleWrap" => Self::ToggleWrap,
+            "ToggleLineEnding" => Self::ToggleLineEnding,
+            "ToggleHex" => Self::ToggleHex,
+            "LoadWholeFile" => Self::LoadWholeFile,
+            //History
+            "Undo" => Self::Undo,
+            "Redo" => Self::Redo,
+            //Search
+            "SearchNext" => Self::SearchNext,
+            "SearchPrev" => Self::SearchPrev,
+            "ClearSearch" => Self::ClearSearch,
+            //Folding
+            "Fold" => Self::Fold,
+            "Unfold" => Self::Unfold,
+            "ToggleAllFolds" => Self::ToggleAllFolds,
+            //Macros
+            "StopMacro" => Self::StopMacro,
 
             "Swap" => Self::Swap, //Swap file buffers
             "Null" => Self::Null,
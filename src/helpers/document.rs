@@ -0,0 +1,252 @@
+use std::{borrow::Cow, fmt::Display};
+
+use ropey::{Rope, RopeSlice};
+
+use super::{graphemes, history::BufferPos};
+
+///Text of a buffer. Backed by a rope, so indexing lines, converting between bytes, chars and lines
+///and editing anywhere are all O(log n) no matter how big the file is.
+///Only '\n' is treated as a line break. Positions are given as (line, byte column)
+#[derive(Debug, Clone, Default)]
+pub struct Document {
+    rope: Rope,
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Self { rope: Rope::new() }
+    }
+    pub fn from_str(s: &str) -> Self {
+        Self {
+            rope: Rope::from_str(s),
+        }
+    }
+    #[inline]
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+    ///Gets how many lines the document has. An empty document still has one line
+    #[inline]
+    pub fn len_lines(&self) -> usize {
+        self.rope.len_lines()
+    }
+    #[inline]
+    pub fn len_bytes(&self) -> usize {
+        self.rope.len_bytes()
+    }
+    #[inline]
+    pub fn len_chars(&self) -> usize {
+        self.rope.len_chars()
+    }
+    ///Gets the line at the given index without its line break
+    pub fn line(&self, idx: usize) -> RopeSlice<'_> {
+        let line = self.rope.line(idx);
+        let len = line.len_bytes();
+        if len > 0 && line.byte(len - 1) == b'\n' {
+            line.byte_slice(..len - 1)
+        } else {
+            line
+        }
+    }
+    ///Gets the size in bytes of the line at the given index without its line break
+    #[inline]
+    pub fn line_len(&self, idx: usize) -> usize {
+        self.line(idx).len_bytes()
+    }
+    ///Gets the line at the given index without its line break as an owned string
+    #[inline]
+    pub fn line_string(&self, idx: usize) -> String {
+        self.line(idx).to_string()
+    }
+    ///Gets the line at the given index without its line break, borrowed from the rope unless it is
+    ///split between chunks
+    pub fn line_str(&self, idx: usize) -> Cow<'_, str> {
+        let line = self.line(idx);
+        match line.as_str() {
+            Some(line) => Cow::Borrowed(line),
+            None => Cow::Owned(line.to_string()),
+        }
+    }
+    #[inline]
+    pub fn line_to_byte(&self, idx: usize) -> usize {
+        self.rope.line_to_byte(idx)
+    }
+    #[inline]
+    pub fn line_to_char(&self, idx: usize) -> usize {
+        self.rope.line_to_char(idx)
+    }
+    #[inline]
+    pub fn byte_to_line(&self, byte: usize) -> usize {
+        self.rope.byte_to_line(byte)
+    }
+    #[inline]
+    pub fn byte_to_char(&self, byte: usize) -> usize {
+        self.rope.byte_to_char(byte)
+    }
+    #[inline]
+    pub fn char_to_byte(&self, idx: usize) -> usize {
+        self.rope.char_to_byte(idx)
+    }
+    #[inline]
+    pub fn char_to_line(&self, idx: usize) -> usize {
+        self.rope.char_to_line(idx)
+    }
+    ///Converts the given position into an absolute byte index
+    #[inline]
+    pub fn pos_to_byte(&self, (y, x): BufferPos) -> usize {
+        self.rope.line_to_byte(y) + x
+    }
+    ///Converts the given absolute byte index into a position
+    pub fn byte_to_pos(&self, byte: usize) -> BufferPos {
+        let y = self.rope.byte_to_line(byte);
+        (y, byte - self.rope.line_to_byte(y))
+    }
+    ///Converts the given position into an absolute char index
+    #[inline]
+    pub fn pos_to_char(&self, pos: BufferPos) -> usize {
+        self.rope.byte_to_char(self.pos_to_byte(pos))
+    }
+    ///Converts the given absolute char index into a position
+    #[inline]
+    pub fn char_to_pos(&self, idx: usize) -> BufferPos {
+        self.byte_to_pos(self.rope.char_to_byte(idx))
+    }
+    ///Gets the display column where the given position is drawn
+    pub fn display_col(&self, (y, x): BufferPos, tab_width: usize) -> usize {
+        graphemes::display_col(&self.line_str(y), x, tab_width)
+    }
+    ///Gets the byte column of the grapheme drawn at the given display column of the line, clamped
+    ///to the line
+    pub fn byte_at_col(&self, y: usize, col: usize, tab_width: usize) -> usize {
+        graphemes::byte_at_col(&self.line_str(y), col, tab_width)
+    }
+    ///Gets the size in bytes of the grapheme starting at the given position. 0 at the end of the
+    ///line
+    pub fn next_grapheme_len(&self, (y, x): BufferPos) -> usize {
+        graphemes::next_grapheme(&self.line_str(y), x) - x
+    }
+    ///Gets the size in bytes of the grapheme that finishes at the given position. 0 at the init of
+    ///the line
+    pub fn prev_grapheme_len(&self, (y, x): BufferPos) -> usize {
+        x - graphemes::prev_grapheme(&self.line_str(y), x)
+    }
+    ///Writes the given text at the given position and returns the position where it finishes
+    pub fn insert(&mut self, at: BufferPos, text: &str) -> BufferPos {
+        let byte = self.pos_to_byte(at);
+        self.rope.insert(self.rope.byte_to_char(byte), text);
+        self.byte_to_pos(byte + text.len())
+    }
+    ///Removes the text between the given positions and returns it
+    pub fn remove(&mut self, from: BufferPos, to: BufferPos) -> String {
+        let removed = self.slice(from, to);
+        let (from, to) = (self.pos_to_char(from), self.pos_to_char(to));
+        self.rope.remove(from..to);
+        removed
+    }
+    ///Gets the text between the given positions
+    pub fn slice(&self, from: BufferPos, to: BufferPos) -> String {
+        let (from, to) = (self.pos_to_byte(from), self.pos_to_byte(to));
+        self.rope.byte_slice(from..to).to_string()
+    }
+    ///Finds the byte where the first occurrence of the given text at or after the given byte
+    ///starts. The rope is searched chunk by chunk, keeping the end of the chunks before to find
+    ///the occurrences split between two of them
+    pub fn find_from(&self, text: &str, from: usize) -> Option<usize> {
+        if text.is_empty() {
+            return None;
+        }
+        let keep = text.len() - 1; //bytes an occurrence can have before the chunk it finishes on
+        let mut tail = String::new();
+        let mut offset = from; //where the current chunk starts
+        for chunk in self.rope.byte_slice(from..).chunks() {
+            if !tail.is_empty() {
+                let head = &chunk[..ceil_char_boundary(chunk, keep)];
+                let joined = tail.clone() + head;
+                if let Some(idx) = joined.find(text).filter(|idx| *idx < tail.len()) {
+                    return Some(offset - tail.len() + idx);
+                }
+            }
+            if let Some(idx) = chunk.find(text) {
+                return Some(offset + idx);
+            }
+            tail.push_str(chunk);
+            tail.drain(..ceil_char_boundary(&tail, tail.len().saturating_sub(keep)));
+            offset += chunk.len();
+        }
+        None
+    }
+    ///Gets the lines inside the given range. The range is clamped into the document bounds
+    pub fn lines(&self, from: usize, to: usize) -> impl Iterator<Item = RopeSlice<'_>> {
+        let to = to.min(self.len_lines());
+        (from.min(to)..to).map(|idx| self.line(idx))
+    }
+}
+
+///Gets the first char boundary of the given text at or after the given byte, clamped to its end
+fn ceil_char_boundary(text: &str, byte: usize) -> usize {
+    (byte.min(text.len())..=text.len())
+        .find(|idx| text.is_char_boundary(*idx))
+        .unwrap_or(text.len())
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for chunk in self.rope.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{graphemes, Document};
+
+    #[test]
+    fn find_from_matches_str_find_across_chunks() {
+        //long enough for the rope to have many chunks, with multibyte chars on their edges
+        let content: String = (0..2000).map(|i| format!("ñ{i}é\n")).collect();
+        let document = Document::from_str(&content);
+        assert!(document.rope().chunks().count() > 4);
+        for text in ["ñ1999é", "é\nñ", "9é\nñ10", "\n", "absent", "ñ0"] {
+            for from in (0..content.len())
+                .filter(|i| content.is_char_boundary(*i))
+                .step_by(97)
+            {
+                let expected = content[from..].find(text).map(|i| from + i);
+                assert_eq!(
+                    document.find_from(text, from),
+                    expected,
+                    "{text:?} from {from}"
+                );
+            }
+        }
+        assert_eq!(document.find_from("", 0), None);
+    }
+
+    #[test]
+    fn columns_of_lines_split_between_chunks() {
+        let long: String = (0..800)
+            .map(|i| if i % 7 == 0 { "\té" } else { "ab" })
+            .collect();
+        let document = Document::from_str(&format!("short\tline\n{long}\nend"));
+        assert!(document.line(1).as_str().is_none());
+        assert!(matches!(document.line_str(0), Cow::Borrowed("short\tline")));
+        assert_eq!(document.line_str(1), long);
+        for x in (0..long.len())
+            .filter(|x| long.is_char_boundary(*x))
+            .step_by(13)
+        {
+            let col = graphemes::display_col(&long, x, 4);
+            assert_eq!(document.display_col((1, x), 4), col);
+            assert_eq!(document.byte_at_col(1, col, 4), x);
+            let next = graphemes::next_grapheme(&long, x) - x;
+            assert_eq!(document.next_grapheme_len((1, x)), next);
+            let prev = x - graphemes::prev_grapheme(&long, x);
+            assert_eq!(document.prev_grapheme_len((1, x)), prev);
+        }
+        assert_eq!(document.display_col((0, 6), 4), 8);
+    }
+}
@@ -0,0 +1,392 @@
+use std::{error::Error, fmt};
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+const BOM: char = '\u{feff}';
+const UTF16_SAMPLE: usize = 4096; //bytes looked at to guess if a file is UTF-16 without BOM
+const BINARY_SAMPLE: usize = 8000; //bytes looked at to guess if a file is binary
+
+///The sequence that ends the lines of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::Crlf => "\r\n",
+        }
+    }
+    ///Gets the other line ending
+    #[inline]
+    pub fn toggled(&self) -> Self {
+        match self {
+            Self::Lf => Self::Crlf,
+            Self::Crlf => Self::Lf,
+        }
+    }
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lf => write!(f, "LF"),
+            Self::Crlf => write!(f, "CRLF"),
+        }
+    }
+}
+
+///Errors turning the bytes of a file into text or back
+#[derive(Debug)]
+pub enum EncodingError {
+    UnknownEncoding(String),
+    Malformed(&'static Encoding), //the bytes are not valid in the encoding
+    Unmappable(&'static Encoding), //the text has chars that can not be written in the encoding
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownEncoding(label) => write!(f, "Unknown encoding {label}"),
+            Self::Malformed(encoding) => write!(f, "The file is not valid {}", encoding.name()),
+            Self::Unmappable(encoding) => {
+                write!(f, "The buffer can not be written as {}", encoding.name())
+            }
+        }
+    }
+}
+
+impl Error for EncodingError {}
+
+///Gets the encoding with the given name, like `utf-8`, `utf-16le` or `latin1`
+pub fn encoding_for_label(label: &str) -> Result<&'static Encoding, EncodingError> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| EncodingError::UnknownEncoding(label.to_string()))
+}
+
+///Checks if the given encoding can write every char, which are the ones a BOM makes sense for
+#[inline]
+fn is_unicode(encoding: &'static Encoding) -> bool {
+    encoding == UTF_8 || encoding == UTF_16LE || encoding == UTF_16BE
+}
+
+///Guesses if the given bytes are UTF-16 without BOM, by the zero bytes ASCII chars have on one of
+///the halves of each unit
+fn guess_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(UTF16_SAMPLE)];
+    if sample.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let units = sample.len() / 2;
+    let (mut high, mut low) = (0, 0); //zeros on the first and on the second byte of each unit
+    for unit in sample.chunks_exact(2) {
+        high += (unit[0] == 0) as usize;
+        low += (unit[1] == 0) as usize;
+    }
+    match (high, low) {
+        (0, low) if low * 2 >= units => Some(UTF_16LE),
+        (high, 0) if high * 2 >= units => Some(UTF_16BE),
+        _ => None,
+    }
+}
+
+///How the content of a file is laid out on disk besides its lines. Buffers keep their content
+///with LF line endings, no BOM and no trailing newline, and this restores them when saving.
+///Files mixing LF and CRLF are kept as LF with the CRs on the content of their lines, so they are
+///saved as they were read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileFormat {
+    pub encoding: &'static Encoding,
+    pub line_ending: LineEnding, //every line is saved with it
+    pub trailing_newline: bool,
+    pub bom: bool,
+    pub mixed_endings: bool, //the file has both LF and CRLF lines
+}
+
+impl Default for FileFormat {
+    fn default() -> Self {
+        Self {
+            encoding: UTF_8,
+            line_ending: LineEnding::default(),
+            trailing_newline: false,
+            bom: false,
+            mixed_endings: false,
+        }
+    }
+}
+
+impl FileFormat {
+    ///Guesses if the given bytes are not text, by having zero bytes that are not explained by
+    ///them being UTF-16
+    pub fn is_binary(bytes: &[u8]) -> bool {
+        let sample = &bytes[..bytes.len().min(BINARY_SAMPLE)];
+        sample.contains(&0) && Encoding::for_bom(bytes).is_none() && guess_utf16(bytes).is_none()
+    }
+    ///Decodes the bytes of a file, detecting its encoding by its BOM, then trying UTF-16 and
+    ///UTF-8, and using the fallback one if none of them fit. UTF-16 goes first as text with the
+    ///zeros it has is valid UTF-8 too
+    pub fn decode(
+        bytes: &[u8],
+        fallback: &'static Encoding,
+    ) -> Result<(Self, String), EncodingError> {
+        let (encoding, bom_len) = match Encoding::for_bom(bytes) {
+            Some(found) => found,
+            None => match guess_utf16(bytes) {
+                Some(utf16) => (utf16, 0),
+                None if std::str::from_utf8(bytes).is_ok() => (UTF_8, 0),
+                None => (fallback, 0),
+            },
+        };
+        Self::decode_from(bytes, encoding, bom_len)
+    }
+    ///Decodes the bytes of a file in the given encoding
+    pub fn decode_as(
+        bytes: &[u8],
+        encoding: &'static Encoding,
+    ) -> Result<(Self, String), EncodingError> {
+        let bom_len = Encoding::for_bom(bytes)
+            .filter(|(found, _)| *found == encoding)
+            .map_or(0, |(_, len)| len);
+        Self::decode_from(bytes, encoding, bom_len)
+    }
+    fn decode_from(
+        bytes: &[u8],
+        encoding: &'static Encoding,
+        bom_len: usize,
+    ) -> Result<(Self, String), EncodingError> {
+        let content = encoding
+            .decode_without_bom_handling_and_without_replacement(&bytes[bom_len..])
+            .ok_or(EncodingError::Malformed(encoding))?;
+        let (mut format, content) = Self::detect(&content);
+        format.encoding = encoding;
+        format.bom = bom_len > 0;
+        Ok((format, content))
+    }
+    ///Encodes the given buffer content as it is written to the file
+    pub fn encode(&self, content: &str) -> Result<Vec<u8>, EncodingError> {
+        let laid = self.apply(content);
+        //encoding_rs only decodes UTF-16, its encoders write UTF-8 instead
+        let bytes = if self.encoding == UTF_16LE {
+            laid.encode_utf16().flat_map(u16::to_le_bytes).collect()
+        } else if self.encoding == UTF_16BE {
+            laid.encode_utf16().flat_map(u16::to_be_bytes).collect()
+        } else {
+            let (bytes, _, unmappable) = self.encoding.encode(&laid);
+            if unmappable {
+                return Err(EncodingError::Unmappable(self.encoding));
+            }
+            bytes.into_owned()
+        };
+        Ok(bytes)
+    }
+    ///Changes the encoding the file is written with. Only Unicode encodings keep the BOM
+    pub fn set_encoding(&mut self, encoding: &'static Encoding) {
+        self.encoding = encoding;
+        self.bom &= is_unicode(encoding);
+    }
+    ///Detects the format of the given UTF-8 file content and returns it with the content as
+    ///buffers keep it
+    pub fn detect(content: &str) -> (Self, String) {
+        let (bom, content) = match content.strip_prefix(BOM) {
+            Some(content) => (true, content),
+            None => (false, content),
+        };
+        let (lf, crlf) = (
+            content.matches('\n').count(),
+            content.matches("\r\n").count(),
+        );
+        let line_ending = if crlf > 0 && crlf == lf {
+            LineEnding::Crlf
+        } else {
+            LineEnding::Lf
+        };
+        //lines of an LF or mixed file that end with a CR keep it as part of their content
+        let mut content = match line_ending {
+            LineEnding::Lf => content.to_string(),
+            LineEnding::Crlf => content.replace("\r\n", "\n"),
+        };
+        let trailing_newline = content.ends_with('\n');
+        if trailing_newline {
+            content.pop();
+        }
+        let format = Self {
+            encoding: UTF_8,
+            line_ending,
+            trailing_newline,
+            bom,
+            mixed_endings: crlf > 0 && crlf < lf,
+        };
+        (format, content)
+    }
+    ///Lays out the given buffer content in this format, before encoding it
+    fn apply(&self, content: &str) -> String {
+        let mut laid = String::with_capacity(content.len() + 4);
+        if self.bom {
+            laid.push(BOM);
+        }
+        match self.line_ending {
+            LineEnding::Lf => laid.push_str(content),
+            LineEnding::Crlf => laid.push_str(&content.replace('\n', "\r\n")),
+        }
+        if self.trailing_newline {
+            laid.push_str(self.line_ending.as_str());
+        }
+        laid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::{UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+    use super::{encoding_for_label, EncodingError, FileFormat, LineEnding};
+
+    ///Decodes the given bytes and checks that encoding them back gives the same bytes
+    fn round_trip(bytes: &[u8]) -> (FileFormat, String) {
+        let (format, content) = FileFormat::decode(bytes, UTF_8).unwrap();
+        assert_eq!(format.encode(&content).unwrap(), bytes);
+        (format, content)
+    }
+
+    #[test]
+    fn lf_with_and_without_trailing_newline() {
+        let (format, content) = round_trip(b"a\nb\n");
+        assert_eq!(content, "a\nb");
+        assert_eq!(format.line_ending, LineEnding::Lf);
+        assert!(format.trailing_newline);
+        let (format, content) = round_trip(b"a\nb");
+        assert_eq!(content, "a\nb");
+        assert!(!format.trailing_newline);
+        let (format, content) = round_trip(b"");
+        assert_eq!(content, "");
+        assert!(!format.trailing_newline);
+    }
+
+    #[test]
+    fn crlf_is_kept_out_of_the_content() {
+        let (format, content) = round_trip(b"a\r\nb\r\n");
+        assert_eq!(content, "a\nb");
+        assert_eq!(format.line_ending, LineEnding::Crlf);
+        assert!(format.trailing_newline && !format.mixed_endings);
+    }
+
+    #[test]
+    fn mixed_endings_are_not_normalised() {
+        let (format, content) = round_trip(b"a\r\nb\nc\r\n");
+        assert_eq!(content, "a\r\nb\nc\r");
+        assert_eq!(format.line_ending, LineEnding::Lf);
+        assert!(format.mixed_endings);
+        let (format, _) = round_trip(b"a\nb\r\n");
+        assert!(format.mixed_endings);
+    }
+
+    #[test]
+    fn utf8_bom_is_kept_out_of_the_content() {
+        let (format, content) = round_trip("\u{feff}a\r\n".as_bytes());
+        assert_eq!(content, "a");
+        assert!(format.bom);
+        assert_eq!(format.encoding, UTF_8);
+        assert_eq!(format.line_ending, LineEnding::Crlf);
+    }
+
+    #[test]
+    fn utf16_with_bom() {
+        let (format, content) = round_trip(&[0xff, 0xfe, b'a', 0, b'\r', 0, b'\n', 0]);
+        assert_eq!(content, "a");
+        assert_eq!(format.encoding, UTF_16LE);
+        assert!(format.bom && format.trailing_newline);
+        assert_eq!(format.line_ending, LineEnding::Crlf);
+        let (format, content) = round_trip(&[0xfe, 0xff, 0, b'a', 0, b'\n', 0, b'b']);
+        assert_eq!(content, "a\nb");
+        assert_eq!(format.encoding, UTF_16BE);
+        assert!(format.bom && !format.trailing_newline);
+    }
+
+    #[test]
+    fn utf16_without_bom_is_guessed() {
+        let le: Vec<u8> = "ab\ncd".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let (format, content) = round_trip(&le);
+        assert_eq!(content, "ab\ncd");
+        assert_eq!(format.encoding, UTF_16LE);
+        assert!(!format.bom);
+        let be: Vec<u8> = "ab\n".encode_utf16().flat_map(u16::to_be_bytes).collect();
+        let (format, content) = round_trip(&be);
+        assert_eq!(content, "ab");
+        assert_eq!(format.encoding, UTF_16BE);
+        assert!(!format.bom && format.trailing_newline);
+    }
+
+    #[test]
+    fn utf16_encodes_chars_outside_the_bmp() {
+        let (format, content) = round_trip(&[0xff, 0xfe, 0x3d, 0xd8, 0x00, 0xde, b'\n', 0]);
+        assert_eq!(content, "\u{1f600}");
+        assert_eq!(format.encoding, UTF_16LE);
+    }
+
+    #[test]
+    fn invalid_utf8_uses_the_fallback() {
+        let latin1 = encoding_for_label("latin1").unwrap();
+        assert_eq!(latin1, WINDOWS_1252);
+        let bytes = b"caf\xe9\n";
+        let (format, content) = FileFormat::decode(bytes, latin1).unwrap();
+        assert_eq!(content, "caf\u{e9}");
+        assert_eq!(format.encoding, latin1);
+        assert_eq!(format.encode(&content).unwrap(), bytes);
+        //valid UTF-8 does not go to the fallback
+        let (format, _) = FileFormat::decode("caf\u{e9}".as_bytes(), latin1).unwrap();
+        assert_eq!(format.encoding, UTF_8);
+    }
+
+    #[test]
+    fn decoding_as_an_encoding() {
+        let (format, content) = FileFormat::decode_as(b"caf\xe9", WINDOWS_1252).unwrap();
+        assert_eq!(content, "caf\u{e9}");
+        assert!(!format.bom);
+        assert!(matches!(
+            FileFormat::decode_as(b"caf\xe9", UTF_8),
+            Err(EncodingError::Malformed(_))
+        ));
+        //the BOM of the encoding is not part of the content
+        let (format, content) = FileFormat::decode_as(&[0xef, 0xbb, 0xbf, b'a'], UTF_8).unwrap();
+        assert!(format.bom);
+        assert_eq!(content, "a");
+    }
+
+    #[test]
+    fn unmappable_chars_are_an_error() {
+        let (mut format, content) = FileFormat::decode(b"a", UTF_8).unwrap();
+        format.set_encoding(WINDOWS_1252);
+        assert!(matches!(
+            format.encode(&format!("{content}\u{3042}")),
+            Err(EncodingError::Unmappable(_))
+        ));
+        assert!(matches!(
+            encoding_for_label("nope"),
+            Err(EncodingError::UnknownEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn only_unicode_encodings_keep_the_bom() {
+        let (mut format, _) = FileFormat::decode(&[0xef, 0xbb, 0xbf, b'a'], UTF_8).unwrap();
+        format.set_encoding(UTF_16BE);
+        assert!(format.bom);
+        format.set_encoding(WINDOWS_1252);
+        assert!(!format.bom);
+    }
+
+    #[test]
+    fn binary_files_are_not_utf16() {
+        assert!(FileFormat::is_binary(&[
+            0x7f, b'E', b'L', b'F', 0, 0, 1, 0, 0x13
+        ]));
+        let utf16: Vec<u8> = "text".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert!(!FileFormat::is_binary(&utf16));
+        assert!(!FileFormat::is_binary(b"plain text"));
+    }
+}
@@ -1,5 +1,7 @@
-use gapbuf::GapBuffer;
+use std::ops::Range;
+
 use ratatui::crossterm::terminal::size;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn terminal_size() -> (u16, u16) {
     size().unwrap()
@@ -11,46 +13,11 @@ pub fn min_max<T: PartialOrd>(a: T, b: T) -> (T, T) {
         (a, b)
     }
 }
-///Gets the size of the char by seeing its first byte considering it as a utf8 byte
-pub fn char_size_init(mut b: u8) -> u8 {
-    if b < 0x80 {
-        1
-    } else {
-        b &= 0b1111_0000;
-        let mut n = 0;
-        while b != 0 {
-            n += 1;
-            b <<= 1;
-        }
-        n - 1
-    }
-}
-///Gets the size of the char at the given start point backwards
-///Example
-///```rust
-///let v = vec![b'a', 0xc3, 0xa0, 0xc3, 0xa7]; //'aàç'
-///char_size_backwards(&v, v.len()-1); //2
-///char_size_backwards(&v, v.len()-3); //2
-///char_size_backwards(&v, 1); // 0; Invalid Byte
-///char_size_backwards(&v, 0); //1
-///```
-pub fn char_size_backwards(vec: &GapBuffer<u8>, spoint: usize) -> usize {
-    if vec[spoint] < 0x80 {
-        1
-    } else {
-        let mut bcount = 0;
-        while bcount < 4 {
-            let mut c = vec[spoint - bcount] & 0b1111_0000;
-            if c > 0b1000_0000 {
-                let mut n = 0;
-                while c != 0 {
-                    n += 1;
-                    c <<= 1;
-                }
-                return n - 1;
-            }
-            bcount += 1;
-        }
-        0
-    }
+///Gets the byte ranges of the words inside the given string following the unicode word
+///boundaries. Punctuation and whitespace are not considered words
+pub fn word_bounds(s: &str) -> Vec<Range<usize>> {
+    s.split_word_bound_indices()
+        .filter(|(_, word)| word.chars().any(char::is_alphanumeric))
+        .map(|(idx, word)| idx..idx + word.len())
+        .collect()
 }
@@ -0,0 +1,82 @@
+use unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
+use unicode_width::UnicodeWidthStr;
+
+//Conversions between the three ways of pointing inside a line: the byte offset, used to store
+//positions; the grapheme index, what the user sees as a single character; and the display column,
+//where it's drawn on the terminal. Byte offsets given to these functions must be at the start of
+//a grapheme, except for `next_grapheme` and `prev_grapheme` which only need a char boundary.
+//Tabs reach the next multiple of the tab width, a tab width of 0 leaves them undrawn
+
+///Gets how many columns the given grapheme takes on the terminal. Control chars are not drawn
+pub fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.chars().any(char::is_control) {
+        0
+    } else {
+        grapheme.width()
+    }
+}
+///Gets how many columns the given grapheme takes when drawn at the given column
+#[inline]
+pub fn grapheme_cols(grapheme: &str, col: usize, tab_width: usize) -> usize {
+    match grapheme {
+        "\t" if tab_width > 0 => tab_width - col % tab_width,
+        _ => grapheme_width(grapheme),
+    }
+}
+///Gets the byte where the grapheme after the given byte starts, the end of the line if there is
+///none
+pub fn next_grapheme(line: &str, byte: usize) -> usize {
+    GraphemeCursor::new(byte, line.len(), true)
+        .next_boundary(line, 0)
+        .ok()
+        .flatten()
+        .unwrap_or(line.len())
+}
+///Gets the byte where the grapheme before the given byte starts, 0 if there is none
+pub fn prev_grapheme(line: &str, byte: usize) -> usize {
+    GraphemeCursor::new(byte, line.len(), true)
+        .prev_boundary(line, 0)
+        .ok()
+        .flatten()
+        .unwrap_or(0)
+}
+///Gets the display column where the grapheme at the given byte is drawn
+pub fn display_col(line: &str, byte: usize, tab_width: usize) -> usize {
+    line[..byte]
+        .graphemes(true)
+        .fold(0, |col, g| col + grapheme_cols(g, col, tab_width))
+}
+///Gets how many columns the whole line takes
+#[inline]
+pub fn display_width(line: &str, tab_width: usize) -> usize {
+    display_col(line, line.len(), tab_width)
+}
+///Gets the given text drawn from the given column, with its tabs as the spaces they take
+pub fn expand_tabs(text: &str, col: usize, tab_width: usize) -> String {
+    if !text.contains('\t') {
+        return text.to_string();
+    }
+    let mut expanded = String::with_capacity(text.len());
+    let mut col = col;
+    for grapheme in text.graphemes(true) {
+        let cols = grapheme_cols(grapheme, col, tab_width);
+        match grapheme {
+            "\t" => expanded.extend(std::iter::repeat_n(' ', cols)),
+            _ => expanded.push_str(grapheme),
+        }
+        col += cols;
+    }
+    expanded
+}
+///Gets the byte of the grapheme drawn at the given display column, the end of the line past it. A
+///column in the middle of a wide grapheme gives where that grapheme starts
+pub fn byte_at_col(line: &str, col: usize, tab_width: usize) -> usize {
+    let mut current = 0;
+    for (byte, grapheme) in line.grapheme_indices(true) {
+        current += grapheme_cols(grapheme, current, tab_width);
+        if current > col {
+            return byte;
+        }
+    }
+    line.len()
+}
@@ -0,0 +1,286 @@
+use std::collections::VecDeque;
+
+///A position inside a buffer as (line, byte column)
+pub type BufferPos = (usize, usize);
+
+///Max amount of undo units kept per buffer
+const HISTORY_LIMIT: usize = 1000;
+
+///A single modification made to a buffer
+#[derive(Debug, Clone)]
+pub enum Edit {
+    Insert { at: BufferPos, content: String },
+    Remove { at: BufferPos, content: String },
+}
+
+impl Edit {
+    ///Gets the position where the content of this edit starts
+    pub fn start(&self) -> BufferPos {
+        match self {
+            Self::Insert { at, .. } | Self::Remove { at, .. } => *at,
+        }
+    }
+    pub fn content(&self) -> &str {
+        match self {
+            Self::Insert { content, .. } | Self::Remove { content, .. } => content,
+        }
+    }
+    ///Gets the position where the content of this edit finishes, as if it was written
+    pub fn end(&self) -> BufferPos {
+        text_end(self.start(), self.content())
+    }
+    ///Checks if the given edit is a continuation of this one, like typing the next char of a
+    ///word or deleting the char before the last deleted one
+    fn continues_with(&self, next: &Edit) -> bool {
+        if self.content().contains('\n') || next.content().contains('\n') {
+            return false;
+        }
+        match (self, next) {
+            (Self::Insert { content, .. }, Self::Insert { at, content: next }) => {
+                //Typing a space after a word closes the word
+                let breaks_word = next.starts_with(char::is_whitespace)
+                    && !content.ends_with(char::is_whitespace);
+                *at == self.end() && !breaks_word
+            }
+            (Self::Remove { at, .. }, Self::Remove { at: nat, content }) => {
+                //backspace removes before the last one, del removes at the same place
+                *nat == *at || (nat.0 == at.0 && nat.1 + content.len() == at.1)
+            }
+            _ => false,
+        }
+    }
+}
+
+///Gets the position where the given text finishes if written at the given position
+pub fn text_end(at: BufferPos, text: &str) -> BufferPos {
+    match text.rfind('\n') {
+        Some(idx) => (at.0 + text.matches('\n').count(), text.len() - idx - 1),
+        None => (at.0, at.1 + text.len()),
+    }
+}
+
+///Edits that are undone and redone together. Keeps the cursor position from before the first
+///edit was made so undoing can restore it
+#[derive(Debug, Clone)]
+pub struct EditGroup {
+    pub edits: Vec<Edit>,
+    pub cursor: BufferPos,
+}
+
+///Journal of the modifications made in a buffer. Consecutive keystrokes are grouped into a single
+///undo unit until the group gets sealed, by a cursor jump, a newline or a mode change.
+#[derive(Debug)]
+pub struct EditHistory {
+    undo: VecDeque<EditGroup>,
+    redo: Vec<EditGroup>,
+    sealed: bool,
+    depth: usize, //how many begin_group calls are waiting for their end_group
+}
+
+impl EditHistory {
+    pub fn new() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            sealed: true,
+            depth: 0,
+        }
+    }
+    ///Records the given edit. The cursor is the position it had before the edit was made
+    pub fn record(&mut self, edit: Edit, cursor: BufferPos) {
+        self.redo.clear();
+        if !self.sealed {
+            if let Some(group) = self.undo.back_mut() {
+                let grouping = self.depth > 0;
+                if grouping || group.edits.last().is_some_and(|l| l.continues_with(&edit)) {
+                    group.edits.push(edit);
+                    return;
+                }
+            }
+        }
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(EditGroup {
+            edits: vec![edit],
+            cursor,
+        });
+        self.sealed = false;
+    }
+    ///Closes the current undo unit, the next edit will start a new one. Does nothing while a
+    ///group is open
+    #[inline]
+    pub fn seal(&mut self) {
+        if self.depth == 0 {
+            self.sealed = true;
+        }
+    }
+    ///Makes every edit recorded until the matching `end_group` belong to a single undo unit.
+    ///Groups can be nested, only the outermost one matters
+    pub fn begin_group(&mut self) {
+        if self.depth == 0 {
+            self.sealed = true;
+        }
+        self.depth += 1;
+    }
+    ///Closes the group opened by `begin_group`
+    pub fn end_group(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        self.seal();
+    }
+    ///Takes the last undo unit, moving it into the redo stack. The caller must revert its edits
+    pub fn undo(&mut self) -> Option<EditGroup> {
+        self.seal();
+        let group = self.undo.pop_back()?;
+        self.redo.push(group.clone());
+        Some(group)
+    }
+    ///Takes the last undone unit, moving it back into the undo stack. The caller must reapply its
+    ///edits
+    pub fn redo(&mut self) -> Option<EditGroup> {
+        self.seal();
+        let group = self.redo.pop()?;
+        self.undo.push_back(group.clone());
+        Some(group)
+    }
+    ///Forgets every recorded edit
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.sealed = true;
+        self.depth = 0;
+    }
+}
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Edit, EditHistory, HISTORY_LIMIT};
+
+    fn insert(at: (usize, usize), content: &str) -> Edit {
+        Edit::Insert {
+            at,
+            content: content.to_string(),
+        }
+    }
+
+    fn remove(at: (usize, usize), content: &str) -> Edit {
+        Edit::Remove {
+            at,
+            content: content.to_string(),
+        }
+    }
+
+    ///Gets the contents of the edits of the next group to undo, keeping it on the history
+    fn last_group(history: &mut EditHistory) -> Vec<String> {
+        let group = history.undo().unwrap();
+        history.redo();
+        group
+            .edits
+            .iter()
+            .map(|e| e.content().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let mut history = EditHistory::new();
+        history.record(insert((0, 0), "a"), (0, 0));
+        history.seal();
+        history.record(insert((0, 1), "b"), (0, 1));
+        let undone = history.undo().unwrap();
+        assert_eq!(undone.cursor, (0, 1));
+        assert_eq!(undone.edits[0].content(), "b");
+        assert_eq!(history.undo().unwrap().edits[0].content(), "a");
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().unwrap().edits[0].content(), "a");
+        assert_eq!(history.redo().unwrap().edits[0].content(), "b");
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn recording_clears_the_redo_stack() {
+        let mut history = EditHistory::new();
+        history.record(insert((0, 0), "a"), (0, 0));
+        history.undo();
+        history.record(insert((0, 0), "b"), (0, 0));
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn typing_and_deleting_runs_are_merged() {
+        let mut history = EditHistory::new();
+        for (i, c) in ["w", "o", "r", "d"].iter().enumerate() {
+            history.record(insert((0, i), c), (0, i));
+        }
+        assert_eq!(last_group(&mut history), ["w", "o", "r", "d"]);
+        //a space after a word starts a new unit
+        history.record(insert((0, 4), " "), (0, 4));
+        assert_eq!(last_group(&mut history), [" "]);
+
+        let mut history = EditHistory::new();
+        //backspace removes before the last removed char, delete at the same place
+        history.record(remove((0, 3), "c"), (0, 4));
+        history.record(remove((0, 2), "b"), (0, 3));
+        history.record(remove((0, 2), "d"), (0, 2));
+        assert_eq!(last_group(&mut history), ["c", "b", "d"]);
+        history.record(remove((0, 0), "a"), (0, 0));
+        assert_eq!(last_group(&mut history), ["a"]);
+    }
+
+    #[test]
+    fn newlines_are_not_merged() {
+        let mut history = EditHistory::new();
+        history.record(insert((0, 0), "a"), (0, 0));
+        history.record(insert((0, 1), "\n"), (0, 1));
+        assert_eq!(last_group(&mut history), ["\n"]);
+    }
+
+    #[test]
+    fn nested_groups_are_a_single_unit() {
+        let mut history = EditHistory::new();
+        history.begin_group();
+        history.record(insert((0, 0), "a"), (0, 0));
+        history.begin_group();
+        history.record(insert((5, 0), "b"), (0, 1));
+        history.end_group();
+        //sealing does nothing while a group is open
+        history.seal();
+        history.record(remove((9, 9), "c"), (0, 1));
+        history.end_group();
+        assert_eq!(last_group(&mut history), ["a", "b", "c"]);
+        assert_eq!(history.undo().unwrap().cursor, (0, 0));
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn edits_after_a_group_start_a_new_unit() {
+        let mut history = EditHistory::new();
+        history.begin_group();
+        history.record(insert((0, 0), "a"), (0, 0));
+        history.end_group();
+        history.record(insert((0, 1), "b"), (0, 1));
+        assert_eq!(last_group(&mut history), ["b"]);
+    }
+
+    #[test]
+    fn old_units_are_dropped_at_the_limit() {
+        let mut history = EditHistory::new();
+        for i in 0..HISTORY_LIMIT + 5 {
+            history.record(insert((i, 0), &i.to_string()), (i, 0));
+            history.seal();
+        }
+        let mut undone = 0;
+        let mut oldest = None;
+        while let Some(group) = history.undo() {
+            oldest = Some(group.edits[0].content().to_string());
+            undone += 1;
+        }
+        assert_eq!(undone, HISTORY_LIMIT);
+        assert_eq!(oldest.unwrap(), "5");
+    }
+}
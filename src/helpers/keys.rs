@@ -0,0 +1,125 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+const MODIFIERS: [(KeyModifiers, &str); 6] = [
+    (KeyModifiers::SHIFT, "Shift"),
+    (KeyModifiers::CONTROL, "Control"),
+    (KeyModifiers::ALT, "Alt"),
+    (KeyModifiers::SUPER, "Super"),
+    (KeyModifiers::HYPER, "Hyper"),
+    (KeyModifiers::META, "Meta"),
+];
+///Keys written by name. The chars isht strings or the notation itself can not hold have one too
+const NAMED: [(KeyCode, &str); 19] = [
+    (KeyCode::Char(' '), "Space"),
+    (KeyCode::Char('-'), "Minus"),
+    (KeyCode::Char('"'), "Quote"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Esc, "Esc"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::BackTab, "BackTab"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Null, "Null"),
+];
+
+///Writes the given key as its modifiers followed by the key, all of them joined by '-', like
+///Control-Shift-Left. Returns None for keys without a name
+pub fn key_name(key: KeyEvent) -> Option<String> {
+    let code = match key.code {
+        KeyCode::F(n) => format!("F{n}"),
+        KeyCode::Char(c) => match NAMED.iter().find(|(named, _)| *named == key.code) {
+            Some((_, name)) => name.to_string(),
+            None => c.to_string(),
+        },
+        code => NAMED
+            .iter()
+            .find(|(named, _)| *named == code)?
+            .1
+            .to_string(),
+    };
+    let mut parts: Vec<String> = MODIFIERS
+        .iter()
+        .filter(|(modifier, _)| key.modifiers.contains(*modifier))
+        .map(|(_, name)| name.to_string())
+        .collect();
+    parts.push(code);
+    Some(parts.join("-"))
+}
+
+///Reads a key written as key_name does
+pub fn parse_key(s: &str) -> Option<KeyEvent> {
+    let mut parts: Vec<&str> = s.split('-').collect();
+    let code = parts.pop()?;
+    let mut modifiers = KeyModifiers::NONE;
+    for part in parts {
+        let (modifier, _) = MODIFIERS.iter().find(|(_, name)| *name == part)?;
+        modifiers |= *modifier;
+    }
+    let mut chars = code.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match NAMED.iter().find(|(_, name)| *name == code) {
+            Some((named, _)) => *named,
+            None => KeyCode::F(code.strip_prefix('F')?.parse().ok()?),
+        },
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{key_name, parse_key, NAMED};
+
+    fn round_trip(key: KeyEvent) -> String {
+        let name = key_name(key).unwrap();
+        assert_eq!(parse_key(&name), Some(key), "{name}");
+        name
+    }
+
+    #[test]
+    fn chars_and_named_keys_round_trip() {
+        assert_eq!(round_trip(KeyEvent::from(KeyCode::Char('a'))), "a");
+        assert_eq!(round_trip(KeyEvent::from(KeyCode::Char('é'))), "é");
+        assert_eq!(round_trip(KeyEvent::from(KeyCode::F(12))), "F12");
+        for (code, name) in NAMED {
+            assert_eq!(round_trip(KeyEvent::from(code)), name);
+        }
+    }
+
+    #[test]
+    fn modifiers_are_written_in_a_fixed_order() {
+        let key = KeyEvent::new(
+            KeyCode::Left,
+            KeyModifiers::ALT | KeyModifiers::SHIFT | KeyModifiers::CONTROL,
+        );
+        assert_eq!(round_trip(key), "Shift-Control-Alt-Left");
+        let key = KeyEvent::new(KeyCode::Char('-'), KeyModifiers::CONTROL);
+        assert_eq!(round_trip(key), "Control-Minus");
+        //the order they are read in does not matter
+        assert_eq!(
+            parse_key("Alt-Shift-Control-Left"),
+            parse_key("Shift-Control-Alt-Left")
+        );
+    }
+
+    #[test]
+    fn unnamed_keys_and_bad_names() {
+        assert_eq!(key_name(KeyEvent::from(KeyCode::CapsLock)), None);
+        assert_eq!(parse_key(""), None);
+        assert_eq!(parse_key("Ctrl-a"), None);
+        assert_eq!(parse_key("Nope"), None);
+        assert_eq!(parse_key("Fx"), None);
+        assert_eq!(parse_key("Control-"), None);
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use isht::configuration::LanguageBindings;
+use tree_sitter::{Language, QueryError};
+use tree_sitter_language::LanguageFn;
+
+use super::syntax::{HighlightQuery, IndentQuery};
+
+///A language known by the editor, with the grammar and the queries used for it
+pub struct LanguageInfo {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+    pub file_names: &'static [&'static str],
+    pub shebangs: &'static [&'static str], //interpreters named on the first line
+    pub grammar: LanguageFn,
+    pub highlights: &'static [&'static str], //joined in order, the first patterns win
+    pub indents: &'static str, //nodes indenting their content as @indent, closing tokens as @outdent
+    pub openers: &'static str, //chars indenting the next line when they end one
+}
+
+impl LanguageInfo {
+    #[inline]
+    pub fn language(&self) -> Language {
+        self.grammar.into()
+    }
+}
+
+pub static LANGUAGES: [LanguageInfo; 7] = [
+    LanguageInfo {
+        name: "javascript",
+        extensions: &["js", "mjs", "cjs", "jsx"],
+        file_names: &[],
+        shebangs: &["node", "deno", "bun"],
+        grammar: tree_sitter_javascript::LANGUAGE,
+        highlights: &[
+            tree_sitter_javascript::JSX_HIGHLIGHT_QUERY,
+            tree_sitter_javascript::HIGHLIGHT_QUERY,
+        ],
+        indents: r#"
+[(statement_block) (class_body) (switch_body) (object) (array) (arguments)
+ (formal_parameters) (template_substitution)] @indent
+["}" "]" ")"] @outdent
+"#,
+        openers: "{([",
+    },
+    LanguageInfo {
+        name: "rust",
+        extensions: &["rs"],
+        file_names: &[],
+        shebangs: &[],
+        grammar: tree_sitter_rust::LANGUAGE,
+        highlights: &[tree_sitter_rust::HIGHLIGHTS_QUERY],
+        indents: r#"
+[(block) (declaration_list) (field_declaration_list) (enum_variant_list) (match_block)
+ (field_initializer_list) (arguments) (parameters) (array_expression) (token_tree)
+ (use_list) (tuple_expression)] @indent
+["}" "]" ")"] @outdent
+"#,
+        openers: "{([",
+    },
+    LanguageInfo {
+        name: "python",
+        extensions: &["py", "pyi", "pyw"],
+        file_names: &["SConstruct", "SConscript"],
+        shebangs: &["python"],
+        grammar: tree_sitter_python::LANGUAGE,
+        highlights: &[tree_sitter_python::HIGHLIGHTS_QUERY],
+        indents: r#"
+[(block) (list) (dictionary) (set) (tuple) (argument_list) (parameters)] @indent
+["}" "]" ")"] @outdent
+"#,
+        openers: "{([:",
+    },
+    LanguageInfo {
+        name: "toml",
+        extensions: &["toml"],
+        file_names: &["Cargo.lock", "Pipfile", "poetry.lock"],
+        shebangs: &[],
+        grammar: tree_sitter_toml_ng::LANGUAGE,
+        highlights: &[tree_sitter_toml_ng::HIGHLIGHTS_QUERY],
+        indents: r#"
+[(array) (inline_table)] @indent
+["}" "]"] @outdent
+"#,
+        openers: "{[",
+    },
+    LanguageInfo {
+        name: "json",
+        extensions: &["json"],
+        file_names: &[".prettierrc", ".babelrc", ".eslintrc", "flake.lock"],
+        shebangs: &[],
+        grammar: tree_sitter_json::LANGUAGE,
+        highlights: &[tree_sitter_json::HIGHLIGHTS_QUERY],
+        indents: r#"
+[(object) (array)] @indent
+["}" "]"] @outdent
+"#,
+        openers: "{[",
+    },
+    LanguageInfo {
+        name: "markdown",
+        extensions: &["md", "markdown"],
+        file_names: &[],
+        shebangs: &[],
+        grammar: tree_sitter_md::LANGUAGE,
+        highlights: &[tree_sitter_md::HIGHLIGHT_QUERY_BLOCK],
+        indents: "",
+        openers: "",
+    },
+    LanguageInfo {
+        name: "bash",
+        extensions: &["sh", "bash", "zsh"],
+        file_names: &[".bashrc", ".bash_profile", ".profile", ".zshrc", "PKGBUILD"],
+        shebangs: &["sh", "bash", "zsh", "dash"],
+        grammar: tree_sitter_bash::LANGUAGE,
+        highlights: &[tree_sitter_bash::HIGHLIGHT_QUERY],
+        indents: r#"
+[(compound_statement) (do_group) (if_statement) (case_statement) (subshell)] @indent
+["}" ")" "fi" "done" "esac" "elif" "else"] @outdent
+"#,
+        openers: "{(",
+    },
+];
+
+///Gets the interpreter named by the shebang of the given line, like `python3` for
+///`#!/usr/bin/env python3`
+fn shebang_interpreter(line: &str) -> Option<&str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        words.find(|w| !w.starts_with('-'))
+    } else {
+        Some(program)
+    }
+}
+
+///Finds out the language of files from their name or their first line. Starts with the languages
+///known by the editor, which can be bound to more files through the `[languages]` group
+pub struct LanguageRegistry {
+    extensions: HashMap<String, usize>,
+    file_names: HashMap<String, usize>,
+    shebangs: HashMap<String, usize>,
+    highlights: [OnceLock<Option<Arc<HighlightQuery>>>; LANGUAGES.len()],
+    indents: [OnceLock<Option<Arc<IndentQuery>>>; LANGUAGES.len()],
+    errors: Mutex<Vec<String>>, //queries that failed to compile, waiting to be logged
+}
+
+impl LanguageRegistry {
+    pub fn new() -> Self {
+        let mut this = Self {
+            extensions: HashMap::new(),
+            file_names: HashMap::new(),
+            shebangs: HashMap::new(),
+            highlights: Default::default(),
+            indents: Default::default(),
+            errors: Mutex::new(Vec::new()),
+        };
+        for (idx, language) in LANGUAGES.iter().enumerate() {
+            let owned =
+                |names: &'static [&'static str]| names.iter().map(move |n| (n.to_string(), idx));
+            this.extensions.extend(owned(language.extensions));
+            this.file_names.extend(owned(language.file_names));
+            this.shebangs.extend(owned(language.shebangs));
+        }
+        this
+    }
+    ///Creates the registry with the bindings of the configuration on top of the default ones.
+    ///Bindings to languages that are not known are ignored
+    pub fn with_bindings(bindings: &LanguageBindings) -> Self {
+        let mut this = Self::new();
+        let known =
+            |(target, name): (&String, &String)| Self::find(name).map(|idx| (target.clone(), idx));
+        this.extensions
+            .extend(bindings.extensions.iter().filter_map(known));
+        this.file_names
+            .extend(bindings.file_names.iter().filter_map(known));
+        this.shebangs
+            .extend(bindings.shebangs.iter().filter_map(known));
+        this
+    }
+    ///Gets the index of the language with the given name
+    pub fn find(name: &str) -> Option<usize> {
+        LANGUAGES
+            .iter()
+            .position(|l| l.name.eq_ignore_ascii_case(name))
+    }
+    #[inline]
+    pub fn info(&self, language: usize) -> &'static LanguageInfo {
+        &LANGUAGES[language]
+    }
+    ///Finds the language of the file at the given path by its name, then its extension and then
+    ///the shebang on its first line
+    pub fn detect(&self, path: Option<&Path>, first_line: &str) -> Option<usize> {
+        let by_path = path.and_then(|path| {
+            let name = path.file_name()?.to_str()?;
+            self.file_names.get(name).or_else(|| {
+                let extension = path.extension()?.to_str()?;
+                self.extensions.get(extension)
+            })
+        });
+        by_path.copied().or_else(|| {
+            let interpreter = shebang_interpreter(first_line)?;
+            //python3.12 is also python
+            let unversioned =
+                interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+            self.shebangs
+                .get(interpreter)
+                .or_else(|| self.shebangs.get(unversioned))
+                .copied()
+        })
+    }
+    ///Gets the highlight query of the given language. It's only compiled the first time it's
+    ///needed and then shared by every buffer
+    pub fn highlight_query(&self, language: usize) -> Option<Arc<HighlightQuery>> {
+        self.highlights[language]
+            .get_or_init(|| {
+                let info = self.info(language);
+                let query = HighlightQuery::new(&info.language(), &info.highlights.join("\n"));
+                self.keep_error(language, "highlight", query)
+            })
+            .clone()
+    }
+    ///Gets the indent query of the given language, compiled the first time it's needed. Languages
+    ///without indentation rules have none
+    pub fn indent_query(&self, language: usize) -> Option<Arc<IndentQuery>> {
+        self.indents[language]
+            .get_or_init(|| {
+                let info = self.info(language);
+                if info.indents.trim().is_empty() {
+                    return None;
+                }
+                self.keep_error(
+                    language,
+                    "indent",
+                    IndentQuery::new(&info.language(), info.indents),
+                )
+            })
+            .clone()
+    }
+    ///Gets the compiled query, keeping the error to be logged if it failed
+    fn keep_error<T>(
+        &self,
+        language: usize,
+        kind: &str,
+        query: Result<T, QueryError>,
+    ) -> Option<Arc<T>> {
+        query
+            .map_err(|e| {
+                let name = self.info(language).name;
+                let msg = format!("The {kind} query of {name} does not compile: {e}");
+                self.errors.lock().unwrap().push(msg);
+            })
+            .ok()
+            .map(Arc::new)
+    }
+    ///Takes the errors of the queries that failed to compile since the last call
+    pub fn take_errors(&self) -> Vec<String> {
+        std::mem::take(&mut *self.errors.lock().unwrap())
+    }
+}
+impl Default for LanguageRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl std::fmt::Debug for LanguageRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LanguageRegistry")
+            .field("extensions", &self.extensions)
+            .field("file_names", &self.file_names)
+            .field("shebangs", &self.shebangs)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LanguageRegistry, LANGUAGES};
+    use crate::helpers::syntax::HighlightQuery;
+
+    #[test]
+    fn bundled_queries_compile() {
+        let registry = LanguageRegistry::new();
+        for (idx, info) in LANGUAGES.iter().enumerate() {
+            assert!(registry.highlight_query(idx).is_some(), "{}", info.name);
+            let indents = registry.indent_query(idx);
+            assert_eq!(
+                indents.is_some(),
+                !info.indents.trim().is_empty(),
+                "{}",
+                info.name
+            );
+        }
+        assert_eq!(registry.take_errors(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn failed_queries_are_kept_to_be_logged() {
+        let registry = LanguageRegistry::new();
+        let rust = LanguageRegistry::find("rust").unwrap();
+        let query = HighlightQuery::new(&LANGUAGES[rust].language(), "(not_a_node) @x");
+        assert!(registry.keep_error(rust, "highlight", query).is_none());
+        let errors = registry.take_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("The highlight query of rust does not compile"));
+        assert!(registry.take_errors().is_empty());
+    }
+
+    #[test]
+    fn languages_are_found_by_name_and_shebang() {
+        let registry = LanguageRegistry::new();
+        let path = std::path::Path::new("src/main.rs");
+        assert_eq!(
+            registry.detect(Some(path), ""),
+            LanguageRegistry::find("rust")
+        );
+        let python = LanguageRegistry::find("python");
+        assert_eq!(
+            registry.detect(None, "#!/usr/bin/env -S python3.12"),
+            python
+        );
+        assert_eq!(registry.detect(None, "plain"), None);
+    }
+}
@@ -1,9 +1,15 @@
+pub mod document;
+pub mod file_format;
 mod functions;
+pub mod graphemes;
+pub mod history;
+pub mod keys;
+pub mod languages;
+pub mod search;
+pub mod syntax;
 mod types;
 mod vec2;
 pub use vec2::Vec2;
-mod file_tree;
 pub mod terminal_line;
-pub use file_tree::FileTree;
 pub use functions::*;
 pub use types::*;
@@ -0,0 +1,299 @@
+use std::{fmt, ops::Range};
+
+use regex::{Regex, RegexBuilder};
+
+///How the pattern of a search is matched against the buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    Literal,
+    CaseInsensitive,
+    Regex,
+    RegexCaseInsensitive,
+}
+
+///A compiled search query. Queries are written as `pattern` or `pattern/flags`, where the flags
+///can be `i` to ignore case and `r` to read the pattern as a regex. A '/' that is part of the
+///pattern must be escaped as `\/`
+#[derive(Debug, Clone)]
+pub struct Search {
+    pattern: String,
+    mode: SearchMode,
+    regex: Regex,
+}
+
+impl Search {
+    pub fn new(query: &str) -> Result<Self, regex::Error> {
+        let (pattern, mode) = Self::parse_query(query);
+        Self::with_mode(pattern, mode)
+    }
+    ///Compiles the given pattern without reading flags from it
+    pub fn with_mode(pattern: String, mode: SearchMode) -> Result<Self, regex::Error> {
+        let source = match mode {
+            SearchMode::Literal | SearchMode::CaseInsensitive => regex::escape(&pattern),
+            SearchMode::Regex | SearchMode::RegexCaseInsensitive => pattern.clone(),
+        };
+        let regex = RegexBuilder::new(&source)
+            .case_insensitive(matches!(
+                mode,
+                SearchMode::CaseInsensitive | SearchMode::RegexCaseInsensitive
+            ))
+            .build()?;
+        Ok(Self {
+            pattern,
+            mode,
+            regex,
+        })
+    }
+    ///Splits the query into its pattern and the mode given by its flags
+    fn parse_query(query: &str) -> (String, SearchMode) {
+        let mut split = None;
+        let mut escaped = false;
+        for (idx, c) in query.char_indices() {
+            match c {
+                '\\' => escaped = !escaped,
+                '/' if !escaped => split = Some(idx),
+                _ => escaped = false,
+            }
+        }
+        let (pattern, flags) = match split {
+            Some(idx) if query[idx + 1..].chars().all(|c| matches!(c, 'i' | 'r')) => {
+                (&query[..idx], &query[idx + 1..])
+            }
+            _ => (query, ""),
+        };
+        let mode = match (flags.contains('r'), flags.contains('i')) {
+            (false, false) => SearchMode::Literal,
+            (false, true) => SearchMode::CaseInsensitive,
+            (true, false) => SearchMode::Regex,
+            (true, true) => SearchMode::RegexCaseInsensitive,
+        };
+        let pattern = if matches!(mode, SearchMode::Literal | SearchMode::CaseInsensitive) {
+            pattern.replace("\\/", "/")
+        } else {
+            pattern.to_string()
+        };
+        (pattern, mode)
+    }
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+    pub fn mode(&self) -> SearchMode {
+        self.mode
+    }
+    pub fn regex(&self) -> &Regex {
+        &self.regex
+    }
+    ///Gets the byte ranges of every match inside the given line. Empty matches are ignored
+    pub fn find_all(&self, line: &str) -> Vec<Range<usize>> {
+        self.regex
+            .find_iter(line)
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect()
+    }
+}
+
+///Splits the given content on every '/' that is not escaped
+fn split_unescaped(content: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut init = 0;
+    let mut escaped = false;
+    for (idx, c) in content.char_indices() {
+        match c {
+            '\\' => escaped = !escaped,
+            '/' if !escaped => {
+                pieces.push(&content[init..idx]);
+                init = idx + 1;
+            }
+            _ => escaped = false,
+        }
+    }
+    pieces.push(&content[init..]);
+    pieces
+}
+
+#[derive(Debug)]
+pub enum ReplaceError {
+    Syntax,
+    UnknownFlag(char),
+    Pattern(regex::Error),
+}
+impl std::error::Error for ReplaceError {}
+impl fmt::Display for ReplaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax => write!(
+                f,
+                "Replace must follow the pattern: %s/pattern/replacement/flags"
+            ),
+            Self::UnknownFlag(c) => write!(f, "Replace does not recognize the flag '{c}'"),
+            Self::Pattern(e) => write!(f, "Invalid replace pattern:\n{e}"),
+        }
+    }
+}
+
+///A replace command, written as `%s/pattern/replacement/flags` to act on the whole buffer or as
+///`s/pattern/replacement/flags` to act on the selection. The pattern is a regex and the
+///replacement can use its capture groups as `$1` or `${name}`. Flags are `g` for replacing every
+///match of a line instead of only the first one, `i` for ignoring case and `c` for asking before
+///each replacement
+#[derive(Debug, Clone)]
+pub struct Replace {
+    pub search: Search,
+    pub replacement: String,
+    pub whole_buffer: bool,
+    pub global: bool,
+    pub confirm: bool,
+}
+
+impl Replace {
+    pub fn parse(cmd: &str) -> Result<Self, ReplaceError> {
+        let (whole_buffer, rest) = if let Some(rest) = cmd.strip_prefix("%s/") {
+            (true, rest)
+        } else if let Some(rest) = cmd.strip_prefix("s/") {
+            (false, rest)
+        } else {
+            return Err(ReplaceError::Syntax);
+        };
+        let pieces = split_unescaped(rest);
+        let (pattern, replacement, flags) = match pieces.as_slice() {
+            [pattern, replacement] => (*pattern, *replacement, ""),
+            [pattern, replacement, flags] => (*pattern, *replacement, *flags),
+            _ => return Err(ReplaceError::Syntax),
+        };
+        if pattern.is_empty() {
+            return Err(ReplaceError::Syntax);
+        }
+        let (mut global, mut confirm, mut ignore_case) = (false, false, false);
+        for flag in flags.chars() {
+            match flag {
+                'g' => global = true,
+                'c' => confirm = true,
+                'i' => ignore_case = true,
+                c => return Err(ReplaceError::UnknownFlag(c)),
+            }
+        }
+        let mode = if ignore_case {
+            SearchMode::RegexCaseInsensitive
+        } else {
+            SearchMode::Regex
+        };
+        Ok(Self {
+            search: Search::with_mode(pattern.to_string(), mode).map_err(ReplaceError::Pattern)?,
+            replacement: replacement.replace("\\/", "/").replace("\\n", "\n"),
+            whole_buffer,
+            global,
+            confirm,
+        })
+    }
+    ///Gets the text that replaces the match of the pattern starting at the given index of the line
+    pub fn expand(&self, line: &str, at: usize) -> Option<(Range<usize>, String)> {
+        let caps = self.search.regex().captures_at(line, at)?;
+        let whole = caps.get(0)?;
+        if whole.start() != at {
+            return None;
+        }
+        let mut dst = String::new();
+        caps.expand(&self.replacement, &mut dst);
+        Some((whole.range(), dst))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Replace, ReplaceError, Search, SearchMode};
+
+    fn parsed(query: &str) -> (String, SearchMode) {
+        Search::parse_query(query)
+    }
+
+    #[test]
+    fn queries_are_split_on_their_last_unescaped_slash() {
+        assert_eq!(parsed("abc"), ("abc".into(), SearchMode::Literal));
+        assert_eq!(parsed("abc/i"), ("abc".into(), SearchMode::CaseInsensitive));
+        assert_eq!(parsed("a.c/r"), ("a.c".into(), SearchMode::Regex));
+        assert_eq!(
+            parsed("a.c/ri"),
+            ("a.c".into(), SearchMode::RegexCaseInsensitive)
+        );
+        //what follows the slash is not flags, so it is part of the pattern
+        assert_eq!(parsed("a/b"), ("a/b".into(), SearchMode::Literal));
+        assert_eq!(parsed("a\\/i"), ("a/i".into(), SearchMode::Literal));
+        assert_eq!(
+            parsed("a\\/b/i"),
+            ("a/b".into(), SearchMode::CaseInsensitive)
+        );
+        //regexes keep the escape
+        assert_eq!(parsed("a\\/b/r"), ("a\\/b".into(), SearchMode::Regex));
+        assert_eq!(
+            parsed("a\\\\/i"),
+            ("a\\\\".into(), SearchMode::CaseInsensitive)
+        );
+        assert_eq!(parsed("/"), ("".into(), SearchMode::Literal));
+    }
+
+    #[test]
+    fn literal_searches_escape_the_pattern() {
+        let search = Search::new("a.c").unwrap();
+        assert_eq!(search.find_all("abc a.c"), vec![(4..7)]);
+        let search = Search::new("A.C/i").unwrap();
+        assert_eq!(search.find_all("abc a.c"), vec![(4..7)]);
+        let search = Search::new("a.c/r").unwrap();
+        assert_eq!(search.find_all("abc a.c"), [0..3, 4..7]);
+        assert!(Search::new("(/r").is_err());
+        assert!(Search::new("(").is_ok());
+    }
+
+    #[test]
+    fn empty_matches_are_ignored() {
+        let search = Search::new("x*/r").unwrap();
+        assert_eq!(search.find_all("axxbx"), [1..3, 4..5]);
+        assert!(search.find_all("abc").is_empty());
+    }
+
+    #[test]
+    fn replace_commands_are_parsed() {
+        let replace = Replace::parse("%s/a(.)/b$1/gci").unwrap();
+        assert!(replace.whole_buffer && replace.global && replace.confirm);
+        assert_eq!(replace.search.mode(), SearchMode::RegexCaseInsensitive);
+        assert_eq!(replace.search.pattern(), "a(.)");
+        assert_eq!(replace.replacement, "b$1");
+        let replace = Replace::parse("s/a/b").unwrap();
+        assert!(!replace.whole_buffer && !replace.global && !replace.confirm);
+        assert_eq!(replace.search.mode(), SearchMode::Regex);
+        let replace = Replace::parse("s/a\\/b/c\\/d\\n/").unwrap();
+        assert_eq!(replace.search.pattern(), "a\\/b");
+        assert_eq!(replace.replacement, "c/d\n");
+    }
+
+    #[test]
+    fn bad_replace_commands() {
+        assert!(matches!(Replace::parse("%s/a"), Err(ReplaceError::Syntax)));
+        assert!(matches!(Replace::parse("x/a/b"), Err(ReplaceError::Syntax)));
+        assert!(matches!(Replace::parse("%s//b"), Err(ReplaceError::Syntax)));
+        assert!(matches!(
+            Replace::parse("%s/a/b/g/x"),
+            Err(ReplaceError::Syntax)
+        ));
+        assert!(matches!(
+            Replace::parse("%s/a/b/gx"),
+            Err(ReplaceError::UnknownFlag('x'))
+        ));
+        assert!(matches!(
+            Replace::parse("%s/(/b"),
+            Err(ReplaceError::Pattern(_))
+        ));
+    }
+
+    #[test]
+    fn replacements_expand_the_captures() {
+        let replace = Replace::parse("%s/(?<k>\\w+)=(\\w+)/$2:${k}/").unwrap();
+        assert_eq!(replace.expand("x a=b", 2), Some((2..5, "b:a".to_string())));
+        //the match must start at the given index
+        assert_eq!(replace.expand("x a=b", 1), None);
+        assert_eq!(
+            replace.expand("a=b c=d", 4),
+            Some((4..7, "d:c".to_string()))
+        );
+    }
+}
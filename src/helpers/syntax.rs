@@ -0,0 +1,479 @@
+use std::{cmp::Reverse, collections::HashMap, ops::Range, sync::Arc};
+
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{InputEdit, Language, Node, Parser, Query, QueryCursor, QueryError, Tree};
+
+use super::document::Document;
+
+///Capture names that get highlighted. A capture is painted with the longest of these names it
+///starts with, so `function.call` is painted as `function`
+pub const HIGHLIGHT_NAMES: [&str; 34] = [
+    "attribute",
+    "boolean",
+    "comment",
+    "constant",
+    "constant.builtin",
+    "constructor",
+    "embedded",
+    "escape",
+    "function",
+    "function.builtin",
+    "function.macro",
+    "function.method",
+    "keyword",
+    "label",
+    "number",
+    "operator",
+    "property",
+    "punctuation",
+    "punctuation.bracket",
+    "punctuation.delimiter",
+    "punctuation.special",
+    "string",
+    "string.special",
+    "tag",
+    "text",
+    "text.literal",
+    "text.reference",
+    "text.title",
+    "text.uri",
+    "type",
+    "type.builtin",
+    "variable",
+    "variable.builtin",
+    "variable.parameter",
+];
+
+///Colors used when the `[colors]` group does not set one for a capture
+const DEFAULT_COLORS: [(&str, u32); 16] = [
+    ("attribute", 0xd19a66),
+    ("boolean", 0xd19a66),
+    ("comment", 0x7f848e),
+    ("constant", 0xd19a66),
+    ("constructor", 0xe5c07b),
+    ("escape", 0x56b6c2),
+    ("function", 0x61afef),
+    ("keyword", 0xc678dd),
+    ("number", 0xd19a66),
+    ("operator", 0x56b6c2),
+    ("property", 0xe06c75),
+    ("string", 0x98c379),
+    ("text.literal", 0x98c379),
+    ("text.title", 0xe06c75),
+    ("text.uri", 0x61afef),
+    ("type", 0xe5c07b),
+];
+
+///Gets the color of the highlight at the given index of `HIGHLIGHT_NAMES`. Colors are configured
+///as `syntax.<capture name>` inside the `[colors]` group, like `syntax.keyword`
+pub fn highlight_color(colors: &HashMap<String, u32>, highlight: usize) -> Option<u32> {
+    let mut name = HIGHLIGHT_NAMES.get(highlight)?.to_string();
+    loop {
+        if let Some(color) = colors.get(&format!("syntax.{name}")) {
+            return Some(*color);
+        }
+        if let Some((_, color)) = DEFAULT_COLORS.iter().find(|(n, _)| *n == name) {
+            return Some(*color);
+        }
+        name.truncate(name.rfind('.')?);
+    }
+}
+
+///Gets the index inside `HIGHLIGHT_NAMES` of the highlight used by the given capture name, which is
+///the longest highlight name the capture starts with
+fn highlight_of(capture: &str) -> Option<usize> {
+    HIGHLIGHT_NAMES
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| {
+            capture
+                .strip_prefix(**name)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+        .max_by_key(|(_, name)| name.len())
+        .map(|(idx, _)| idx)
+}
+
+///Highlight query of a language with the highlight used by each of its captures
+pub struct HighlightQuery {
+    query: Query,
+    highlights: Vec<Option<usize>>,
+}
+
+impl HighlightQuery {
+    pub fn new(language: &Language, source: &str) -> Result<Self, QueryError> {
+        let query = Query::new(language, source)?;
+        let highlights = query
+            .capture_names()
+            .iter()
+            .map(|name| highlight_of(name))
+            .collect();
+        Ok(Self { query, highlights })
+    }
+}
+
+///Indent query of a language with its capture of the nodes that indent their content and the
+///one of the tokens closing them
+pub struct IndentQuery {
+    query: Query,
+    indent: Option<u32>,
+    outdent: Option<u32>,
+}
+
+impl IndentQuery {
+    pub fn new(language: &Language, source: &str) -> Result<Self, QueryError> {
+        let query = Query::new(language, source)?;
+        let indent = query.capture_index_for_name("indent");
+        let outdent = query.capture_index_for_name("outdent");
+        Ok(Self {
+            query,
+            indent,
+            outdent,
+        })
+    }
+}
+
+///What the syntax tree tells about the indentation of text starting at some byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndentHint {
+    pub anchor: Option<usize>, //line the text goes a level deeper than, None at the top level
+    pub outdent: bool,         //the text starts with a token closing the node around it
+    pub broken: bool,          //the text before has syntax errors, so the anchor can't be trusted
+}
+
+///Gets the text of the nodes of the given document, as queries need it to check predicates
+fn node_text<'a>(document: &'a Document) -> impl FnMut(Node) -> std::vec::IntoIter<&'a [u8]> {
+    let rope = document.rope();
+    move |node: Node| {
+        rope.byte_slice(node.byte_range())
+            .chunks()
+            .map(str::as_bytes)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+///Checks if tokens wrap the content of the given node, like the braces of a block
+#[inline]
+fn is_wrapped(node: Node) -> bool {
+    node.child(0).is_some_and(|first| !first.is_named())
+}
+
+///Gets the node the indentation of an `@indent` node is counted from: itself if tokens wrap its
+///content or its parent if it starts with its content, like a python block does
+fn indent_owner(node: Node) -> Node {
+    match node.parent() {
+        Some(parent) if !is_wrapped(node) => parent,
+        _ => node,
+    }
+}
+
+///Turns the given captures, sorted by start with the outer ones first, into the ranges each
+///highlight is seen on, in order. Captures are nodes, so they are nested into each other, and the
+///inner ones, or the later ones for the same node, are drawn on top of the others
+fn flatten(found: &[(Range<usize>, usize, usize)], spans: &mut Vec<(Range<usize>, usize)>) {
+    spans.clear();
+    let mut push = |range: Range<usize>, h: usize| {
+        if range.is_empty() {
+            return;
+        }
+        match spans.last_mut() {
+            Some((last, last_h)) if last.end == range.start && *last_h == h => last.end = range.end,
+            _ => spans.push((range, h)),
+        }
+    };
+    let mut around: Vec<(usize, usize)> = Vec::new(); //ends and highlights of the open captures
+    let mut pos = 0; //where the part not pushed yet starts
+    for (range, _, h) in found {
+        while let Some(&(end, top)) = around.last().filter(|(end, _)| *end <= range.start) {
+            push(pos..end, top);
+            pos = pos.max(end);
+            around.pop();
+        }
+        if let Some(&(_, top)) = around.last() {
+            push(pos..range.start, top);
+        }
+        pos = pos.max(range.start);
+        around.push((range.end, *h));
+    }
+    while let Some((end, top)) = around.pop() {
+        push(pos..end, top);
+        pos = pos.max(end);
+    }
+}
+
+///Syntax tree of a buffer. Edits made on the buffer are given to the tree and it only gets parsed
+///again when needed, reusing the parts of the old tree that were not edited
+pub struct Syntax {
+    parser: Parser,
+    tree: Option<Tree>,
+    outdated: bool, //edited after the last parse
+    highlights: Arc<HighlightQuery>,
+    indents: Option<Arc<IndentQuery>>,
+    cursor: QueryCursor,
+    found: Vec<(Range<usize>, usize, usize)>, //captures of the last highlights call, reused
+    spans: Vec<(Range<usize>, usize)>,        //the highlight seen on each range of them, reused
+}
+
+impl Syntax {
+    pub fn new(
+        language: &Language,
+        highlights: Arc<HighlightQuery>,
+        indents: Option<Arc<IndentQuery>>,
+    ) -> Option<Self> {
+        let mut parser = Parser::new();
+        parser.set_language(language).ok()?;
+        Some(Self {
+            parser,
+            tree: None,
+            outdated: true,
+            highlights,
+            indents,
+            cursor: QueryCursor::new(),
+            found: Vec::new(),
+            spans: Vec::new(),
+        })
+    }
+    ///Tells the tree about an edit made on the buffer
+    pub fn edit(&mut self, edit: &InputEdit) {
+        if let Some(tree) = &mut self.tree {
+            tree.edit(edit);
+        }
+        self.outdated = true;
+    }
+    ///Forgets the tree, the next parse starts from nothing. Used when the whole content changes
+    pub fn reset(&mut self) {
+        self.tree = None;
+        self.outdated = true;
+    }
+    ///Parses the document again if it was edited since the last parse
+    pub fn update(&mut self, document: &Document) {
+        if !self.outdated {
+            return;
+        }
+        let rope = document.rope();
+        let tree = self.parser.parse_with(
+            &mut |byte, _| {
+                if byte >= rope.len_bytes() {
+                    return &[][..];
+                }
+                let (chunk, init, _, _) = rope.chunk_at_byte(byte);
+                &chunk.as_bytes()[byte - init..]
+            },
+            self.tree.as_ref(),
+        );
+        self.tree = tree;
+        self.outdated = false;
+    }
+    ///Gets the syntax tree, parsing the document first if needed
+    pub fn tree(&mut self, document: &Document) -> Option<&Tree> {
+        self.update(document);
+        self.tree.as_ref()
+    }
+    ///Gets the highlights of each line inside the given range, relative to the line start, with
+    ///the index of their highlight inside `HIGHLIGHT_NAMES`. Only that range gets queried. Inner
+    ///nodes are on top of the outer ones and, for the same node, the first pattern wins
+    pub fn highlights(
+        &mut self,
+        document: &Document,
+        lines: Range<usize>,
+    ) -> Vec<Vec<(Range<usize>, usize)>> {
+        let lines = lines.start..lines.end.min(document.len_lines());
+        if lines.is_empty() {
+            return Vec::new();
+        }
+        self.update(document);
+        let init = document.line_to_byte(lines.start);
+        let end = document.line_to_byte(lines.end - 1) + document.line_len(lines.end - 1);
+        self.found.clear();
+        if let Some(tree) = &self.tree {
+            self.cursor.set_byte_range(init..end);
+            let mut captures = self.cursor.captures(
+                &self.highlights.query,
+                tree.root_node(),
+                node_text(document),
+            );
+            while let Some((m, idx)) = captures.next() {
+                let capture = m.captures[*idx];
+                if let Some(h) = self.highlights.highlights[capture.index as usize] {
+                    let range = capture.node.byte_range();
+                    let range = range.start.max(init)..range.end.min(end).max(init);
+                    self.found.push((range, m.pattern_index, h));
+                }
+            }
+        }
+        self.found.sort_by_key(|(range, pattern, _)| {
+            (range.start, Reverse(range.end), Reverse(*pattern))
+        });
+        flatten(&self.found, &mut self.spans);
+        let spans = &self.spans;
+        let mut first = 0; //first span that does not finish before the current line
+        lines
+            .map(|idx| {
+                let line_init = document.line_to_byte(idx);
+                let line_end = line_init + document.line_len(idx);
+                while spans
+                    .get(first)
+                    .is_some_and(|(range, _)| range.end <= line_init)
+                {
+                    first += 1;
+                }
+                spans[first..]
+                    .iter()
+                    .take_while(|(range, _)| range.start < line_end)
+                    .map(|(range, h)| {
+                        let start = range.start.max(line_init) - line_init;
+                        (start..range.end.min(line_end) - line_init, *h)
+                    })
+                    .filter(|(range, _)| !range.is_empty())
+                    .collect()
+            })
+            .collect()
+    }
+    ///Finds how text starting at the given byte is indented from the indent query. It goes a level
+    ///deeper than the line where the owner of the innermost `@indent` node around it starts. None
+    ///if the language has no indent query
+    pub fn indent_hint(&mut self, document: &Document, byte: usize) -> Option<IndentHint> {
+        self.update(document);
+        let (Some(indents), Some(tree)) = (&self.indents, &self.tree) else {
+            return None;
+        };
+        let rope = document.rope();
+        let root = tree.root_node();
+        //the tree is broken there if the last token before the text is inside an error
+        let char_idx = rope.byte_to_char(byte);
+        let blanks = rope
+            .chars_at(char_idx)
+            .reversed()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        let last = (blanks < char_idx).then(|| rope.char_to_byte(char_idx - blanks - 1));
+        let mut node = last.and_then(|last| root.descendant_for_byte_range(last, last + 1));
+        let mut broken = false;
+        while let Some(current) = node {
+            broken |= current.is_error() || current.is_missing();
+            node = current.parent();
+        }
+        //the captures reach the content after the byte, where python blocks start
+        let blanks_after = rope
+            .chars_at(char_idx)
+            .take_while(|c| c.is_whitespace())
+            .count();
+        let content = rope.char_to_byte(char_idx + blanks_after);
+        let mut innermost: Option<(Node, usize)> = None; //the owner of the node and its length
+        let mut outdent = false;
+        self.cursor
+            .set_byte_range(byte.saturating_sub(1)..content + 1);
+        let mut captures = self
+            .cursor
+            .captures(&indents.query, root, node_text(document));
+        while let Some((m, idx)) = captures.next() {
+            let capture = m.captures[*idx];
+            let node = capture.node;
+            if Some(capture.index) == indents.outdent && node.start_byte() == byte {
+                outdent = true;
+            }
+            if Some(capture.index) != indents.indent {
+                continue;
+            }
+            let wrapped = is_wrapped(node);
+            let owner = indent_owner(node);
+            let inside = owner.start_byte() < byte
+                && (byte < node.end_byte() || (!wrapped && byte == node.end_byte()));
+            let len = node.end_byte() - owner.start_byte();
+            if inside && innermost.is_none_or(|(_, shortest)| len <= shortest) {
+                innermost = Some((owner, len));
+            }
+        }
+        Some(IndentHint {
+            anchor: innermost.map(|(owner, _)| owner.start_position().row),
+            outdent,
+            broken,
+        })
+    }
+}
+
+impl std::fmt::Debug for Syntax {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Syntax")
+            .field("outdated", &self.outdated)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use super::{flatten, highlight_of, Syntax};
+    use crate::helpers::{document::Document, languages::LanguageRegistry};
+
+    ///Paints every byte with the last capture covering it, the way flatten must see them
+    fn painted(found: &[(Range<usize>, usize, usize)], len: usize) -> Vec<Option<usize>> {
+        let mut painted = vec![None; len];
+        for (range, _, h) in found {
+            for byte in &mut painted[range.clone()] {
+                *byte = Some(*h);
+            }
+        }
+        painted
+    }
+
+    fn flattened(found: &[(Range<usize>, usize, usize)], len: usize) -> Vec<Option<usize>> {
+        let mut spans = Vec::new();
+        flatten(found, &mut spans);
+        let mut painted = vec![None; len];
+        for (range, h) in spans {
+            for byte in &mut painted[range] {
+                *byte = Some(h);
+            }
+        }
+        painted
+    }
+
+    #[test]
+    fn inner_and_later_captures_are_on_top() {
+        let found = [
+            (0..10, 0, 1),
+            (0..10, 1, 2), //the same node, drawn over the previous one
+            (2..4, 0, 3),
+            (3..4, 0, 4),
+            (6..8, 0, 3),
+            (12..14, 0, 5),
+        ];
+        assert_eq!(flattened(&found, 16), painted(&found, 16));
+        let mut spans = Vec::new();
+        flatten(&found, &mut spans);
+        assert_eq!(
+            spans,
+            [
+                (0..2, 2),
+                (2..3, 3),
+                (3..4, 4),
+                (4..6, 2),
+                (6..8, 3),
+                (8..10, 2),
+                (12..14, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn highlights_split_captures_by_line() {
+        let registry = LanguageRegistry::new();
+        let rust = LanguageRegistry::find("rust").unwrap();
+        let highlights = registry.highlight_query(rust).unwrap();
+        let language = registry.info(rust).language();
+        let mut syntax = Syntax::new(&language, highlights, None).unwrap();
+        let document = Document::from_str("/* a\nb */ fn f() {}\nlet s = \"x\";");
+        let lines = syntax.highlights(&document, 0..3);
+        let comment = highlight_of("comment").unwrap();
+        let keyword = highlight_of("keyword").unwrap();
+        assert_eq!(lines[0], [(0..4, comment)]);
+        assert_eq!(lines[1][0], (0..4, comment));
+        assert!(lines[1].contains(&(5..7, keyword)));
+        assert!(lines[2].contains(&(0..3, keyword)));
+        //only the lines asked for are given
+        assert_eq!(syntax.highlights(&document, 1..2), [lines[1].clone()]);
+    }
+}
@@ -2,12 +2,11 @@ use core::fmt;
 use std::{
     fmt::{Debug, Display},
     ops::RangeBounds,
-    str::Chars,
 };
 
 use gapbuf::GapBuffer;
 
-use super::{char_size_backwards, char_size_init};
+use super::graphemes::{next_grapheme, prev_grapheme};
 
 // A wrapper to make easier the manipulation in Ishtar Buffer Lines
 // It used to be a wrapper to VecDeque<char> but since sizeof(char) == 4 in rust, it's better
@@ -86,7 +85,7 @@ impl TerminalLine {
         }
         target
     }
-    ///Inserts the given char at the specific index asserting utf8 encoding
+    ///Inserts the given char at the given byte index, which must be at a char boundary
     pub fn insert(&mut self, idx: usize, c: char) {
         let b = &mut [0; 4];
         let buf = c.encode_utf8(b).as_bytes();
@@ -97,36 +96,17 @@ impl TerminalLine {
         self.buffer.set_gap(idx);
         self.buffer.insert_many(idx, buf.iter().cloned());
     }
-    ///Removes the char at the given idx, backwards if true and returns the char removed and its
-    ///size. Returns none if the index is out of bounds
-    pub fn remove(&mut self, idx: usize, backwards: bool) -> Option<(char, usize)> {
-        if let Some(c) = self.buffer.get(idx) {
-            if c.is_ascii() {
-                self.buffer.set_gap(idx);
-                Some((self.buffer.remove(idx) as char, 1))
-            } else {
-                let mut byte: u32 = 0;
-                if backwards {
-                    let size = char_size_backwards(&self.buffer, idx);
-                    for i in 0..size - 1 {
-                        byte |= self.buffer.remove(idx - i) as u32;
-                        byte <<= 8;
-                    }
-                    byte |= self.buffer.remove(idx - size + 1) as u32;
-                    Some((char::from_u32(byte).unwrap(), size))
-                } else {
-                    let size = char_size_init(*self.buffer.get(idx).unwrap()) as usize;
-                    for _ in 0..size + 1 {
-                        byte |= self.buffer.remove(idx) as u32;
-                        byte <<= 8;
-                    }
-                    byte >>= 8;
-                    Some((char::from_u32(byte).unwrap(), size))
-                }
-            }
-        } else {
-            None
-        }
+    ///Removes the grapheme that finishes at the given byte index if backwards, the one starting at
+    ///it otherwise, and returns it. Returns none if there is no grapheme there
+    pub fn remove(&mut self, idx: usize, backwards: bool) -> Option<String> {
+        let content = self.to_string();
+        let range = match backwards {
+            true if idx > 0 && idx <= content.len() => prev_grapheme(&content, idx)..idx,
+            false if idx < content.len() => idx..next_grapheme(&content, idx),
+            _ => return None,
+        };
+        self.buffer.drain(range.clone());
+        Some(content[range].to_string())
     }
     #[inline]
     pub fn push_back(&mut self, c: char) {
@@ -153,32 +133,18 @@ impl TerminalLine {
         }
     }
     #[inline]
-    pub fn pop_back(&mut self) -> Option<(char, usize)> {
-        self.remove(self.len() - 1, true)
-    }
-    pub fn pop_front(&mut self) -> Option<(char, usize)> {
-        if let Some(b) = self.get(0) {
-            if b.is_ascii() {
-                self.remove(0, false)
-            } else {
-                let mut b = *b;
-                let mut n = 0;
-                while b != 0 {
-                    b <<= 0;
-                    n += 1;
-                }
-                self.remove(n - 1, false)
-            }
-        } else {
-            None
-        }
+    pub fn pop_back(&mut self) -> Option<String> {
+        self.remove(self.len(), true)
+    }
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<String> {
+        self.remove(0, false)
     }
     pub fn append_line(&mut self, line: TerminalLine) {
         if self.buffer.capacity() < self.buffer.len() + line.len() {
             self.buffer.reserve(line.len());
         }
-        self.buffer
-            .insert_many(self.buffer.len(), line.buffer.into_iter());
+        self.buffer.insert_many(self.buffer.len(), line.buffer);
     }
     #[inline]
     pub fn clear(&mut self) {
@@ -4,4 +4,5 @@ pub enum IshtarMode {
     Cmd,
     Modify,
     Selection,
+    BlockSelection, //selects a rectangle of text
 }
@@ -1,5 +1,5 @@
 use std::{
-    fmt::{Arguments, Display},
+    fmt::Display,
     fs::File,
     io::Write,
     ops::{Deref, DerefMut},
@@ -9,12 +9,10 @@ pub enum LogLevel {
     Info,
     Warn,
     Error,
-    Fatal,
 }
 
 pub struct IshtarLogger {
     f: File,
-    queue: String,
 }
 /// Simple logging manager. Logs the contents received into "./tmp/log.txt". In future will be
 /// replaced by OUTDIR flag while building
@@ -28,41 +26,16 @@ impl IshtarLogger {
             } else {
                 File::create_new(f_path).unwrap()
             },
-            queue: String::new(),
         })
     }
-    fn log_queue(&mut self, level: LogLevel) -> usize {
-        let current_time = chrono::prelude::Local::now();
-        let content = format!("{level:?}: {}. Sent at {current_time}\n", self.queue);
-        self.queue.clear();
-        self.f.write(content.as_bytes()).unwrap()
-    }
     fn log_data<T: Display>(&mut self, data: T, level: LogLevel) -> usize {
         let current_time = chrono::prelude::Utc::now().naive_local();
         let content = format!("{level:?}: {data}; at {current_time:?}\n");
         self.f.write(content.as_bytes()).unwrap()
     }
-    pub fn queue_char(&mut self, c: char) {
-        self.queue.push(c);
-    }
-    pub fn queue<T: ToString>(&mut self, val: &T) {
-        self.queue.push_str(&val.to_string());
-    }
-    pub fn flush(&mut self, level: LogLevel) -> (usize, usize) {
-        let len = self.queue.len();
-        let bytes = self.log_queue(level);
-        (len, bytes)
-    }
     pub fn display<T: Display>(&mut self, p: T, level: LogLevel) -> usize {
         self.log_data(p, level)
     }
-    pub fn debug(&mut self, p: Arguments<'_>, level: LogLevel) -> usize {
-        self.log_data(format_args!("{}", p), level)
-    }
-    pub fn buffer(&mut self, buf: &[u8], level: LogLevel) -> usize {
-        let buf = String::from_utf8_lossy(buf);
-        self.log_data(buf, level)
-    }
 }
 impl Deref for IshtarLogger {
     type Target = File;
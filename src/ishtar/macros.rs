@@ -0,0 +1,190 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::OpenOptions,
+    io::Write,
+};
+
+use isht::{CmdTask, ConfigStatment};
+use ratatui::crossterm::event::KeyEvent;
+
+use crate::helpers::keys::{key_name, parse_key};
+
+use super::{logger::LogLevel, Ishtar};
+
+///Keys pressed at most after a single key, so a macro that plays itself comes to an end
+const MAX_REPLAYED_KEYS: usize = 100_000;
+///Keybind subgroups by mode id
+const MODE_NAMES: [&str; 3] = ["normal", "modify", "selection"];
+
+///Keys recorded into a register
+pub struct Macro {
+    mode: usize, //id of the mode the recording started on
+    keys: Vec<KeyEvent>,
+}
+
+///Registers of recorded keys and the keys waiting to be pressed by them
+#[derive(Default)]
+pub struct Macros {
+    registers: HashMap<char, Macro>,
+    recording: Option<(char, Macro)>,
+    staged: Vec<KeyEvent>, //pressed by the tasks of the current key, after it is handled
+    pending: VecDeque<KeyEvent>,
+}
+
+impl Macros {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+    ///Appends the given key to the macro being recorded
+    pub fn record(&mut self, key: KeyEvent) {
+        if let Some((_, recording)) = &mut self.recording {
+            recording.keys.push(key);
+        }
+    }
+}
+
+///Checks if the given text can be written as the name of a keybind in isht
+fn valid_keybind(keybind: &str) -> bool {
+    keybind.starts_with(|c: char| c.is_ascii_alphabetic())
+        && keybind
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && CmdTask::new(keybind).is_err()
+}
+
+impl Ishtar {
+    ///Starts recording the keys pressed from the next one on into the given register
+    pub fn record_macro(&mut self, register: char) {
+        if let Some((current, _)) = &self.macros.recording {
+            let msg = format!("Already recording into register {current}");
+            self.report_error(&msg);
+            return;
+        }
+        let recording = Macro {
+            mode: self.mode_id(),
+            keys: Vec::new(),
+        };
+        self.macros.recording = Some((register, recording));
+        self.handler.cmd_mut().set_recording(true);
+        let msg = format!("Recording into register {register}");
+        self.display(&msg, LogLevel::Info);
+        self.handler.cmd_mut().show(&msg);
+    }
+    ///Stops recording, keeping the keys recorded in their register. The key stopping it is not
+    ///part of the macro
+    pub fn stop_macro(&mut self) {
+        let Some((register, recorded)) = self.macros.recording.take() else {
+            self.report_error("No macro is being recorded");
+            return;
+        };
+        self.handler.cmd_mut().set_recording(false);
+        let msg = format!(
+            "Recorded {} key(s) into register {register}",
+            recorded.keys.len()
+        );
+        self.display(&msg, LogLevel::Info);
+        self.handler.cmd_mut().show(&msg);
+        self.macros.registers.insert(register, recorded);
+    }
+    ///Presses the keys of the given register the given times, once the current key is handled
+    pub fn play_macro(&mut self, register: char, times: u32) {
+        let Some(recorded) = self.macros.registers.get(&register) else {
+            self.report_error(&format!("Register {register} has no macro"));
+            return;
+        };
+        for _ in 0..times {
+            self.macros.staged.extend(&recorded.keys);
+        }
+    }
+    ///Presses the key with the given name, once the current key is handled
+    pub fn press_key(&mut self, name: &str) {
+        match parse_key(name) {
+            Some(key) => self.macros.staged.push(key),
+            None => self.report_error(&format!("Unknown key {name}")),
+        }
+    }
+    ///Binds the macro of the given register to the given keybind, on the mode it was recorded on,
+    ///and appends the binding to the configuration file so it is kept
+    pub fn save_macro(&mut self, register: char, keybind: &str) {
+        if !valid_keybind(keybind) {
+            self.report_error(&format!("Invalid keybind {keybind}"));
+            return;
+        }
+        let Some(recorded) = self.macros.registers.get(&register) else {
+            self.report_error(&format!("Register {register} has no macro"));
+            return;
+        };
+        let mode = recorded.mode;
+        let Some(keys) = recorded
+            .keys
+            .iter()
+            .map(|key| key_name(*key))
+            .collect::<Option<Vec<String>>>()
+        else {
+            self.report_error(&format!("Register {register} has keys without a name"));
+            return;
+        };
+        let presses: String = keys
+            .iter()
+            .map(|key| format!("  PressKey -> \"{key}\"\n"))
+            .collect();
+        let entry = format!(
+            "\n[keybinds]\n\n<{}>\n\n{keybind} = {{\n{presses}}}\n",
+            MODE_NAMES[mode]
+        );
+        let tasks = keys
+            .into_iter()
+            .map(|key| ConfigStatment::Task(CmdTask::PressKey(key)))
+            .collect();
+        self.handler
+            .keybind_mut()
+            .bind(keybind.to_string(), mode, tasks);
+        let Some(path) = Self::config_path() else {
+            self.report_error("Could not save the macro: HOME is not set");
+            return;
+        };
+        let saved = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| OpenOptions::new().create(true).append(true).open(&path))
+            .and_then(|mut file| file.write_all(entry.as_bytes()));
+        match saved {
+            Ok(()) => {
+                let msg = format!("Bound register {register} to {keybind}");
+                self.display(&msg, LogLevel::Info);
+                self.handler.cmd_mut().show(&msg);
+            }
+            Err(e) => self.report_error(&format!("Could not save the macro: {e}")),
+        }
+    }
+    ///Presses the keys the last key handled asked for, and the ones those ask for in turn, the
+    ///same way as if they were typed. Stops at the first key whose tasks fail
+    pub(super) fn replay_keys(&mut self) {
+        let mut replayed = 0;
+        loop {
+            if self.failed {
+                if !self.macros.staged.is_empty() || !self.macros.pending.is_empty() {
+                    self.display("Stopped pressing the keys left", LogLevel::Info);
+                }
+                self.macros.staged.clear();
+                self.macros.pending.clear();
+                return;
+            }
+            //the keys asked for by a key are pressed before the ones left
+            for key in self.macros.staged.drain(..).rev() {
+                self.macros.pending.push_front(key);
+            }
+            let Some(key) = self.macros.pending.pop_front() else {
+                return;
+            };
+            if replayed == MAX_REPLAYED_KEYS {
+                self.warn(&format!(
+                    "Pressed {MAX_REPLAYED_KEYS} keys in a row, a macro may be playing itself"
+                ));
+                continue;
+            }
+            replayed += 1;
+            self.dispatch_key(key);
+        }
+    }
+}
@@ -1,9 +1,11 @@
 mod enums;
 mod logger;
+mod macros;
 mod widget_manager;
 mod widgets;
 use isht::{configuration::IshtarConfiguration, CmdTask, ConfigStatment};
 use logger::{IshtarLogger, LogLevel};
+use macros::Macros;
 use std::{
     env,
     ops::{Deref, DerefMut},
@@ -13,12 +15,14 @@ use std::{
 use widget_manager::WidgetManager;
 use widgets::{
     clipboard::IshtarClipboard,
+    clipboard_history::ClipboardHistory,
     file_manager::{FileManager, ManagingMode},
     keybind_handler::KeybindHandler,
+    text_area::{ReplaceAnswer, TextArea},
     IshtarSelectable,
 };
 
-use crate::helpers::terminal_size;
+use crate::helpers::{search::Replace, terminal_size};
 
 use self::{
     enums::IshtarMode,
@@ -43,37 +47,46 @@ pub struct Ishtar {
     mode: IshtarMode,
     clipboard: IshtarClipboard,
     size: (u16, u16),
+    macros: Macros,
+    failed: bool, //a task of the current key could not be done
 }
 impl Default for Ishtar {
     fn default() -> Self {
         let size = terminal_size();
+        let handler = WidgetManager::new();
+        let history = handler.writer().settings().clipboard_history;
         Self {
             size,
             current_path: env::current_dir().unwrap(),
             exit: false,
             cursor: (0, size.1 as usize),
-            saved_cursor: (0, size.1 as usize),
+            saved_cursor: (0, 0),
             priority: (
                 CommandInterpreter::priority_static(),
                 CommandInterpreter::priority_static(),
             ),
             logger_area: IshtarLogger::new().unwrap(),
-            clipboard: IshtarClipboard::new(),
+            clipboard: IshtarClipboard::with_history(history),
             mode: IshtarMode::Cmd,
-            handler: WidgetManager::new(),
+            handler,
+            macros: Macros::default(),
+            failed: false,
         }
     }
 }
 
 impl Ishtar {
+    ///Gets the path of the configuration file, located as ~/.config/ishtar/config.isht, with the
+    ///home directory taken from HOME. None if it is not set
+    pub fn config_path() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME").filter(|home| !home.is_empty())?;
+        Some(PathBuf::from(home).join(".config/ishtar/config.isht"))
+    }
     ///Gets the configurations based on the configuration file located as
     ///~/.config/ishtar/config.isht. If not given,
     ///uses default.
     pub fn get_configs() -> IshtarConfiguration {
-        let username = std::env::var("USER").unwrap();
-        let path = format!("/home/{username}/.config/ishtar/config.isht");
-        let file_path = std::path::Path::new(&path);
-        if let Ok(content) = std::fs::read_to_string(file_path) {
+        if let Some(content) = Self::config_path().and_then(|p| std::fs::read_to_string(p).ok()) {
             //parses the file and creates a configutation
             IshtarConfiguration::from_content(content).unwrap()
         } else {
@@ -126,17 +139,14 @@ impl Ishtar {
         self.priority.0 == T::priority_static()
     }
 
-    #[inline]
-    pub fn set_cursor_at(&mut self, x: usize, y: usize) {
-        self.cursor.0 = x;
-        self.cursor.1 = y;
-    }
     ///Gets the x position of the cursor
     fn x_cursor_position(&self) -> u16 {
         (self.cursor.0
             + match self.mode {
                 IshtarMode::Cmd => 0,
-                IshtarMode::Modify | IshtarMode::Selection => self.handler.writer().xoffset(),
+                IshtarMode::Modify | IshtarMode::Selection | IshtarMode::BlockSelection => {
+                    self.handler.writer().xoffset()
+                }
             }) as u16
     }
 
@@ -156,7 +166,7 @@ impl Ishtar {
                 self.cursor.0 = self.handler.cmd().cursor();
                 self.cursor.1 = (self.size.1 - 1) as usize;
             }
-            IshtarMode::Modify | IshtarMode::Selection => {
+            IshtarMode::Modify | IshtarMode::Selection | IshtarMode::BlockSelection => {
                 self.cursor = self.handler.writer().cursor();
             }
         };
@@ -167,18 +177,14 @@ impl Ishtar {
             logger::LogLevel::Info,
         );
         match mode {
-            IshtarMode::Modify | IshtarMode::Selection => {
+            IshtarMode::Modify | IshtarMode::Selection | IshtarMode::BlockSelection => {
                 self.handler.cmd_mut().set(&format!("{mode:?}"));
-                let x = self.saved_cursor.0;
-                let y = self.saved_cursor.1;
                 let writer = self.handler.writer_mut();
-                if matches!(mode, IshtarMode::Modify) {
-                    writer.enter_writing()
-                } else {
-                    writer.enter_selection();
-                };
-                writer.set_cursor_x(x);
-                writer.set_cursor_y(y);
+                match mode {
+                    IshtarMode::Modify => writer.enter_writing(),
+                    IshtarMode::BlockSelection => writer.enter_block_selection(),
+                    _ => writer.enter_selection(),
+                }
                 self.set_priority::<WriteableArea>();
             }
             IshtarMode::Cmd => {
@@ -189,23 +195,70 @@ impl Ishtar {
         }
         self.mode = mode;
     }
-    ///Saves the content of the current area at current_path + current_area_file
-    pub fn save_file(&self) -> std::io::Result<()> {
-        self.handler.writer().save(&self.current_path)?;
-        Ok(())
-    }
-
     pub fn mode_id(&self) -> usize {
         match self.mode {
             IshtarMode::Cmd => 0,
             IshtarMode::Modify => 1,
-            IshtarMode::Selection => 2,
+            IshtarMode::Selection | IshtarMode::BlockSelection => 2,
         }
     }
 
-    ///Saves the position of the cursor based on the writer
+    ///Saves the position of the cursor inside the writer buffer
     pub fn save_position(&mut self) {
-        self.saved_cursor = self.handler.writer().cursor();
+        let writer = self.handler.writer();
+        self.saved_cursor = (writer.x(), writer.y());
+    }
+    ///Moves the writer cursor to the last saved position
+    pub fn move_saved(&mut self) {
+        let (x, y) = self.saved_cursor;
+        let writer = self.handler.writer_mut();
+        writer.set_cursor_y(y);
+        writer.set_cursor_x(x);
+    }
+
+    ///Runs the given replace command on the current buffer. With the confirm flag, the command
+    ///line starts asking about each match
+    pub fn replace(&mut self, cmd: &str) {
+        let replace = match Replace::parse(cmd) {
+            Ok(replace) => replace,
+            Err(e) => {
+                self.report_error(&e.to_string());
+                return;
+            }
+        };
+        if !replace.confirm {
+            let count = self.handler.writer_mut().replace_all(&replace);
+            self.report_replace(count);
+        } else if self.handler.writer_mut().begin_replace(replace) {
+            self.set_priority::<CommandInterpreter>();
+            self.handler
+                .cmd_mut()
+                .request_data("Replace? (y/n/a/q) ", CmdTask::ReqReplace);
+        } else {
+            self.report_replace(0);
+        }
+    }
+    fn report_replace(&mut self, count: usize) {
+        let msg = format!("Replaced {count} occurrence(s)");
+        self.display(&msg, LogLevel::Info);
+        self.handler.cmd_mut().show(&msg);
+    }
+    ///Logs the given failure, which stops pressing the keys a macro has left
+    fn warn(&mut self, msg: &str) {
+        self.display(msg, LogLevel::Warn);
+        self.failed = true;
+    }
+    ///Logs the given error and shows it on the command line
+    fn report_error(&mut self, msg: &str) {
+        self.display(msg, LogLevel::Error);
+        self.failed = true;
+        self.handler.cmd_mut().show(msg);
+    }
+
+    ///Pastes the given content on the current buffer
+    fn paste(&mut self, content: &str) {
+        let task = self.handler.writer_mut().paste(content);
+        self.handle_task(&task);
     }
 
     ///Run the given command as a child process
@@ -249,6 +302,7 @@ impl Ishtar {
             CmdTask::CopySelection | CmdTask::CopyToSys | CmdTask::CopyToEditor => {
                 let data = self.handler.writer_mut().get_selection();
                 if let Some(data) = data {
+                    self.clipboard.remember(data.clone());
                     if matches!(task, CmdTask::CopyToSys | CmdTask::CopySelection) {
                         self.clipboard.set(data);
                     } else {
@@ -257,6 +311,54 @@ impl Ishtar {
                 }
                 self.handle_task(&CmdTask::EnterModify);
             }
+            CmdTask::DeleteSelection => {
+                if let Some(removed) = self.handler.writer_mut().delete_selection() {
+                    self.clipboard.remember(removed);
+                    self.change_mode(IshtarMode::Modify);
+                }
+            }
+            CmdTask::ExpandSelection => {
+                if !matches!(self.mode, IshtarMode::Selection) {
+                    self.change_mode(IshtarMode::Selection);
+                }
+                self.handler.writer_mut().expand_selection();
+            }
+            CmdTask::ShrinkSelection => {
+                self.handler.writer_mut().shrink_selection();
+            }
+            CmdTask::Reindent => self.handler.writer_mut().reindent(),
+            CmdTask::AddCursorAbove => {
+                self.handler.writer_mut().add_cursor_above();
+            }
+            CmdTask::AddCursorBelow => {
+                self.handler.writer_mut().add_cursor_below();
+            }
+            CmdTask::AddNextOccurrence => {
+                if !matches!(self.mode, IshtarMode::Selection) {
+                    self.change_mode(IshtarMode::Selection);
+                }
+                if !self.handler.writer_mut().select_next_occurrence() {
+                    self.warn("No other occurrence found");
+                }
+            }
+            CmdTask::SplitSelection => {
+                self.handler.writer_mut().split_selection();
+            }
+            CmdTask::ClearCursors => {
+                self.handler.writer_mut().clear_cursors();
+            }
+            CmdTask::Fold => {
+                self.handler.writer_mut().fold();
+            }
+            CmdTask::Unfold => {
+                self.handler.writer_mut().unfold();
+            }
+            CmdTask::ToggleAllFolds => self.handler.writer_mut().toggle_all_folds(),
+            CmdTask::RecordMacro(register) => self.record_macro(*register),
+            CmdTask::StopMacro => self.stop_macro(),
+            CmdTask::PlayMacro(register, times) => self.play_macro(*register, *times),
+            CmdTask::SaveMacro(register, keybind) => self.save_macro(*register, keybind),
+            CmdTask::PressKey(name) => self.press_key(name),
             CmdTask::SelectLine => {
                 self.handler.writer_mut().goto_init_of_line();
                 self.change_mode(IshtarMode::Selection);
@@ -268,14 +370,76 @@ impl Ishtar {
                 } else {
                     self.clipboard.get()
                 };
-                let data = self.handler.writer_mut().paste(&content);
-                self.handle_task(&data);
+                self.paste(&content);
+            }
+            CmdTask::CopyToRegister(name) | CmdTask::AppendToRegister(name) => {
+                let Some(data) = self.handler.writer_mut().get_selection() else {
+                    self.report_error("Nothing is selected");
+                    return;
+                };
+                self.clipboard.remember(data.clone());
+                if matches!(task, CmdTask::AppendToRegister(_)) {
+                    self.clipboard.append_register(*name, data);
+                } else {
+                    self.clipboard.set_register(*name, data);
+                }
+            }
+            CmdTask::PasteRegister(name) => match self.clipboard.register(*name).cloned() {
+                Some(content) => self.paste(&content),
+                None => self.report_error(&format!("Register {name} is empty")),
+            },
+            CmdTask::ShowHistory => {
+                let entries: Vec<String> = self.clipboard.history().iter().cloned().collect();
+                if entries.is_empty() {
+                    self.report_error("Nothing was copied or deleted yet");
+                    return;
+                }
+                self.priority.1 = self.priority.0;
+                self.set_priority::<ClipboardHistory>();
+                self.handler.clipboard_history_mut().open(entries);
+            }
+            CmdTask::CloseHistory => {
+                self.handler.clipboard_history_mut().close();
+                self.priority.0 = self.priority.1;
+            }
+            CmdTask::PasteHistory(idx) => {
+                match self.clipboard.history().get(*idx as usize).cloned() {
+                    Some(content) => self.paste(&content),
+                    None => self.report_error(&format!("The history has no entry {idx}")),
+                }
             }
 
-            CmdTask::DeleteLine => self.handler.writer_mut().delete_line(),
+            CmdTask::DeleteLine => {
+                let removed = self.handler.writer_mut().delete_line();
+                self.clipboard.remember(removed);
+            }
+            CmdTask::Undo => self.handler.writer_mut().undo(),
+            CmdTask::Redo => self.handler.writer_mut().redo(),
+            CmdTask::Search(query) => {
+                if let Err(e) = self.handler.writer_mut().search(query) {
+                    self.warn(&e.to_string());
+                }
+            }
+            CmdTask::SearchNext | CmdTask::SearchPrev => {
+                let forward = matches!(task, CmdTask::SearchNext);
+                if !self.handler.writer_mut().search_next(forward) {
+                    self.warn("No match found");
+                }
+            }
+            CmdTask::ClearSearch => self.handler.writer_mut().clear_search(),
+            CmdTask::Replace(cmd) => self.replace(cmd),
+            CmdTask::ReplaceAnswer(c) => {
+                let Some(answer) = ReplaceAnswer::from_char(*c) else {
+                    return;
+                };
+                if let Some(count) = self.handler.writer_mut().answer_replace(answer) {
+                    self.handler.cmd_mut().cancel_request();
+                    self.report_replace(count);
+                }
+            }
 
             CmdTask::SavePos => self.save_position(),
-            CmdTask::MoveSaved => self.set_cursor_at(self.saved_cursor.0, self.saved_cursor.1),
+            CmdTask::MoveSaved => self.move_saved(),
 
             CmdTask::Write(content) => {
                 self.handler.writer_mut().paste(content);
@@ -293,24 +457,95 @@ impl Ishtar {
                 let _ = self.exec_cmd(cmd);
             }
             CmdTask::ExecutePrompt(prompt) => {
-                self.handler.cmd_mut().execute_cmd(prompt);
+                let task = self.handler.cmd_mut().execute_cmd(prompt);
+                self.handle_task(&task);
             }
-            CmdTask::MoveIOL => self.handler.writer_mut().goto_init_of_line(),
-            CmdTask::MoveEOL => self.handler.writer_mut().goto_end_of_line(),
+            CmdTask::MoveIOW => self
+                .handler
+                .writer_mut()
+                .at_every_cursor(TextArea::goto_init_of_word),
+            CmdTask::MoveEOW => self
+                .handler
+                .writer_mut()
+                .at_every_cursor(TextArea::goto_end_of_word),
+            CmdTask::MoveIOL => self
+                .handler
+                .writer_mut()
+                .at_every_cursor(TextArea::goto_init_of_line),
+            CmdTask::MoveEOL => self
+                .handler
+                .writer_mut()
+                .at_every_cursor(TextArea::goto_end_of_line),
             CmdTask::MoveIOB => self.handler.writer_mut().goto_init_of_file(),
             CmdTask::MoveEOB => self.handler.writer_mut().goto_end_of_file(),
-            CmdTask::MoveToLine(n) => self.handler.writer_mut().move_y(*n as i16),
+            CmdTask::MatchBracket => {
+                self.handler.writer_mut().match_bracket();
+            }
+            CmdTask::ScrollUp => self.handler.writer_mut().scroll(-1, 0),
+            CmdTask::ScrollDown => self.handler.writer_mut().scroll(1, 0),
+            CmdTask::ScrollLeft => self.handler.writer_mut().scroll(0, -1),
+            CmdTask::ScrollRight => self.handler.writer_mut().scroll(0, 1),
+            CmdTask::ToggleWrap => self.handler.writer_mut().toggle_wrap(),
+            CmdTask::ToggleLineEnding => {
+                let ending = self.handler.writer_mut().toggle_line_ending();
+                self.handler
+                    .cmd_mut()
+                    .show(&format!("Line endings: {ending}"));
+            }
+            CmdTask::MoveToLine(n) => {
+                if !self.handler.writer_mut().goto_line(*n as usize) {
+                    let msg = match self.handler.writer().large().and_then(|l| l.progress()) {
+                        Some(progress) => format!("Line {n} is not indexed yet ({progress}%)"),
+                        None => format!("Line {n} is past the end of the file"),
+                    };
+                    self.report_error(&msg);
+                }
+            }
             CmdTask::MoveToRow(n) => self.handler.writer_mut().move_x(*n as i16),
             CmdTask::EnterNormal => self.change_mode(IshtarMode::Cmd),
             CmdTask::EnterModify => self.change_mode(IshtarMode::Modify),
             CmdTask::EnterSelection => self.change_mode(IshtarMode::Selection),
+            CmdTask::EnterBlockSelection => self.change_mode(IshtarMode::BlockSelection),
 
             CmdTask::ModifyFile(f) => {
-                self.handler.writer_mut().open_file(f.into());
+                if let Err(e) = self.handler.writer_mut().open_file(f.into()) {
+                    self.report_error(&format!("Could not open {f}: {e}"));
+                } else if self.handler.writer().format().mixed_endings {
+                    self.handler
+                        .cmd_mut()
+                        .show("Mixed line endings, kept as they are until toggled");
+                }
+            }
+            CmdTask::ReopenWithEncoding(label) => {
+                match self.handler.writer_mut().reopen_with_encoding(label) {
+                    Ok(()) => self.handler.cmd_mut().show(&format!("Reopened as {label}")),
+                    Err(e) => self.report_error(&format!("Could not reopen the file: {e}")),
+                }
+            }
+            CmdTask::SetEncoding(label) => match self.handler.writer_mut().set_encoding(label) {
+                Ok(name) => self.handler.cmd_mut().show(&format!("Encoding: {name}")),
+                Err(e) => self.report_error(&e.to_string()),
+            },
+            CmdTask::ToggleHex => {
+                if let Err(e) = self.handler.writer_mut().toggle_hex() {
+                    self.report_error(&e.to_string());
+                }
+            }
+            CmdTask::LoadWholeFile => {
+                if let Err(e) = self.handler.writer_mut().load_whole_file() {
+                    self.report_error(&format!("Could not load the file: {e}"));
+                }
+            }
+            CmdTask::GotoOffset(offset) => {
+                if !self.handler.writer_mut().goto_offset(*offset) {
+                    self.report_error(&format!("Offset {offset:#x} is past the end of the file"));
+                }
             }
             CmdTask::SaveFile => {
                 if self.handler.writer().file_name().is_some() {
-                    self.handler.writer().save(&self.current_path).unwrap();
+                    if let Err(e) = self.handler.writer().save(&self.current_path) {
+                        self.report_error(&format!("Could not save: {e}"));
+                    }
                 } else {
                     self.set_priority::<CommandInterpreter>();
                     self.handler
@@ -321,7 +556,9 @@ impl Ishtar {
             CmdTask::SaveFileAs(msg) => {
                 let writer = self.handler.writer_mut();
                 writer.modify_file_name(msg);
-                writer.save(&self.current_path).unwrap();
+                if let Err(e) = writer.save(&self.current_path) {
+                    self.report_error(&format!("Could not save: {e}"));
+                }
             }
             CmdTask::Multi(tasks) => {
                 for task in tasks {
@@ -331,9 +568,7 @@ impl Ishtar {
             CmdTask::Log(s) => {
                 self.display(s, logger::LogLevel::Info);
             }
-            CmdTask::Warn(s) => {
-                self.display(s, logger::LogLevel::Warn);
-            }
+            CmdTask::Warn(s) => self.warn(s),
             CmdTask::ReqSearchCurr => self.request_search(false),
             CmdTask::ReqSearchRoot => self.request_search(true),
             CmdTask::StopSearch => self.stop_search(),
@@ -362,6 +597,7 @@ impl Ishtar {
     fn should_init_keybind(&mut self, key: KeyEvent) -> CmdTask {
         // If got modifier start listening
         if !key.modifiers.is_empty() && !self.handler.keybind().listening() {
+            let previous = self.priority.0;
             self.set_priority::<KeybindHandler>();
             self.handler
                 .keybind_mut()
@@ -375,6 +611,10 @@ impl Ishtar {
             {
                 self.handle_tasks(&tasks);
                 self.handler.keybind_mut().stop_listening();
+                //the next keys go back where they went unless the keybind moved them
+                if self.is_priority_of::<KeybindHandler>() {
+                    self.priority.0 = previous;
+                }
             }
             return CmdTask::Null;
         }
@@ -390,17 +630,35 @@ impl Ishtar {
         CmdTask::Continue
     }
 
+    ///Handles a key typed, recording it into the macro being recorded. The keys its tasks ask
+    ///for are pressed after it, then the syntax queries that failed to compile are logged
     fn handle_key(&mut self, key: KeyEvent) {
+        let recording = self.macros.is_recording();
+        self.failed = false;
+        self.dispatch_key(key);
+        //the keys starting and stopping the recording are not part of it
+        if recording {
+            self.macros.record(key);
+        }
+        self.replay_keys();
+        for error in self.handler.writer().languages().take_errors() {
+            self.display(error, LogLevel::Error);
+        }
+    }
+
+    ///Sends the given key to the widget it goes to and handles the resulting task
+    fn dispatch_key(&mut self, key: KeyEvent) {
         if let KeyCode::Char(c) = key.code {
             if self.is_priority_of::<WriteableArea>()
                 && c.is_uppercase()
                 && key.modifiers == KeyModifiers::SHIFT
             {
-                self.handler.writer_mut().write_char(c);
+                let task = self.handler.writer_mut().keydown(key.code);
+                self.handle_task(&task);
                 return;
             }
         }
-        if !self.is_priority_of::<FileManager>() {
+        if !self.is_priority_of::<FileManager>() && !self.is_priority_of::<ClipboardHistory>() {
             if let CmdTask::Null = self.should_init_keybind(key) {
                 return;
             }
@@ -409,7 +667,7 @@ impl Ishtar {
         let task = widget.keydown(key.code);
         self.handle_task(&task);
         match self.mode {
-            IshtarMode::Modify | IshtarMode::Selection => {
+            IshtarMode::Modify | IshtarMode::Selection | IshtarMode::BlockSelection => {
                 self.cursor = self.handler.writer().cursor();
             }
 
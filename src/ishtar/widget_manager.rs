@@ -1,9 +1,10 @@
-use crate::helpers::terminal_size;
+use crate::helpers::{languages::LanguageRegistry, terminal_size};
 
 use super::{
     widgets::{
-        command_interpreter::CommandInterpreter, file_manager::FileManager,
-        keybind_handler::KeybindHandler, writeable_area::WriteableArea, IshtarSelectable,
+        clipboard_history::ClipboardHistory, command_interpreter::CommandInterpreter,
+        file_manager::FileManager, keybind_handler::KeybindHandler, writeable_area::WriteableArea,
+        IshtarSelectable,
     },
     Ishtar,
 };
@@ -17,31 +18,26 @@ impl WidgetManager {
         let size = terminal_size();
         let configs = Ishtar::get_configs();
         let colors = std::sync::Arc::new(configs.colors);
+        let languages = std::sync::Arc::new(LanguageRegistry::with_bindings(&configs.languages));
         Self {
             widgets: vec![
                 Box::new(WriteableArea::new_vertical(
                     size.0,
                     size.1 - 1,
                     colors.clone(),
+                    languages,
+                    std::sync::Arc::new(configs.settings),
                 )),
                 Box::new(CommandInterpreter::new(colors.clone())),
                 Box::new(KeybindHandler::new(configs.keybinds, colors.clone())),
-                Box::new(FileManager::new_vertical(
-                    true,
+                Box::new(FileManager::new(
                     std::env::current_dir().unwrap(),
-                    colors,
+                    colors.clone(),
                 )),
+                Box::new(ClipboardHistory::new(colors)),
             ],
         }
     }
-    fn find_widget(&self, priority: u8) -> &Box<dyn IshtarSelectable> {
-        for idx in 0..self.widgets.len() {
-            if self.widgets[idx].priority() == priority {
-                return &self.widgets[idx];
-            }
-        }
-        panic!("Failed to find widget with given priority");
-    }
     pub fn find_widget_mut(&mut self, priority: u8) -> &mut Box<dyn IshtarSelectable> {
         for idx in 0..self.widgets.len() {
             if self.widgets[idx].priority() == priority {
@@ -90,4 +86,7 @@ impl WidgetManager {
     pub fn file_manager_mut(&mut self) -> &mut FileManager {
         self.get_widget_mut()
     }
+    pub fn clipboard_history_mut(&mut self) -> &mut ClipboardHistory {
+        self.get_widget_mut()
+    }
 }
@@ -1,8 +1,16 @@
+use std::collections::{HashMap, VecDeque};
+
 use copypasta::{ClipboardContext, ClipboardProvider};
 
+///Entries kept in the history when not configured
+const DEFAULT_HISTORY: usize = 20;
+
 pub struct IshtarClipboard {
     service: ClipboardContext,
     virtual_clip: String,
+    registers: HashMap<char, String>, //named from a to z
+    history: VecDeque<String>,        //last copied and deleted texts, the newest first
+    history_size: usize,
 }
 impl Default for IshtarClipboard {
     fn default() -> Self {
@@ -10,12 +18,20 @@ impl Default for IshtarClipboard {
     }
 }
 ///A manager for the clipboard, that saves directly into the clipboard or into the vritual
-///clipboard. The virtual clipboard is simply a string managed by the application
+///clipboard. The virtual clipboard is simply a string managed by the application. Texts can also
+///be kept in named registers, and the last ones copied or deleted are kept in a history
 impl IshtarClipboard {
     pub fn new() -> Self {
+        Self::with_history(DEFAULT_HISTORY)
+    }
+    ///Creates a clipboard keeping the given number of entries in its history
+    pub fn with_history(history_size: usize) -> Self {
         Self {
             service: copypasta::ClipboardContext::new().unwrap(),
             virtual_clip: String::new(),
+            registers: HashMap::new(),
+            history: VecDeque::new(),
+            history_size,
         }
     }
     ///Gets the content in the clipboard
@@ -61,4 +77,33 @@ impl IshtarClipboard {
             self.virtual_clip.clear();
         }
     }
+    ///Gets the content of the given register, if something was copied into it
+    pub fn register(&self, name: char) -> Option<&String> {
+        self.registers.get(&name)
+    }
+    ///Sets the given content into the given register
+    pub fn set_register<S: Into<String>>(&mut self, name: char, content: S) {
+        self.registers.insert(name, content.into());
+    }
+    ///Appends the given content into the given register
+    pub fn append_register<S: Into<String>>(&mut self, name: char, content: S) {
+        self.registers
+            .entry(name)
+            .or_default()
+            .push_str(&content.into());
+    }
+    ///Keeps the given copied or deleted text as the newest entry of the history, forgetting the
+    ///oldest one when full. The same text twice in a row is kept once
+    pub fn remember<S: Into<String>>(&mut self, content: S) {
+        let content = content.into();
+        if content.is_empty() || self.history.front() == Some(&content) {
+            return;
+        }
+        self.history.push_front(content);
+        self.history.truncate(self.history_size);
+    }
+    ///Gets the texts of the history, the newest first
+    pub fn history(&self) -> &VecDeque<String> {
+        &self.history
+    }
 }
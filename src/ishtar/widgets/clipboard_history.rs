@@ -0,0 +1,150 @@
+use std::{collections::HashMap, sync::Arc};
+
+use isht::CmdTask;
+use ratatui::{
+    crossterm::event::KeyCode,
+    layout::Rect,
+    style::Color,
+    widgets::{Borders, Widget},
+    Frame,
+};
+
+use super::{popup::PopUp, IshtarSelectable};
+
+///A browser of the last copied and deleted texts, shown in a popup, to paste one of them
+pub struct ClipboardHistory {
+    entries: Vec<String>, //the newest first
+    selected: usize,
+    opened: bool,
+    colors: Arc<HashMap<String, u32>>,
+}
+
+impl ClipboardHistory {
+    pub fn new(colors: Arc<HashMap<String, u32>>) -> Self {
+        Self {
+            entries: Vec::new(),
+            selected: 0,
+            opened: false,
+            colors,
+        }
+    }
+    ///Shows the given entries, selecting the newest one
+    pub fn open(&mut self, entries: Vec<String>) {
+        self.entries = entries;
+        self.selected = 0;
+        self.opened = true;
+    }
+    ///Hides the browser
+    pub fn close(&mut self) {
+        self.opened = false;
+    }
+    fn color(&self, name: &str) -> Color {
+        Color::from_u32(self.colors.get(name).cloned().unwrap_or(0xffffff))
+    }
+    ///Writes the given entry in a single line that fits in the given width, showing the line
+    ///breaks it has as ⏎
+    fn preview(entry: &str, width: usize) -> String {
+        let line = entry.replace('\n', "⏎").replace('\t', " ");
+        if line.chars().count() <= width {
+            return line;
+        }
+        let mut cut: String = line.chars().take(width.saturating_sub(1)).collect();
+        cut.push('…');
+        cut
+    }
+}
+
+impl IshtarSelectable for ClipboardHistory {
+    fn priority(&self) -> u8 {
+        4
+    }
+
+    fn priority_static() -> u8
+    where
+        Self: Sized,
+    {
+        4
+    }
+
+    fn can_render(&self) -> bool {
+        self.opened
+    }
+
+    fn keydown(&mut self, key: KeyCode) -> CmdTask {
+        match key {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.entries.len().saturating_sub(1))
+            }
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = self.entries.len().saturating_sub(1),
+            KeyCode::Enter => {
+                return CmdTask::Multi(vec![
+                    CmdTask::CloseHistory,
+                    CmdTask::PasteHistory(self.selected as u32),
+                ])
+            }
+            KeyCode::Esc => return CmdTask::CloseHistory,
+            _ => {}
+        }
+        CmdTask::Null
+    }
+
+    fn renderize(&self, frame: &mut Frame, area: Rect) {
+        let width = (area.width * 2 / 3).max(20).min(area.width);
+        //the entries fit between the borders, scrolling to keep the selected one in sight
+        let rows = ((area.height * 2 / 3).max(3).min(area.height) as usize).saturating_sub(2);
+        if rows == 0 {
+            return;
+        }
+        let height = self.entries.len().min(rows) as u16 + 2;
+        let first = self.selected.saturating_sub(rows.saturating_sub(1));
+        let content = self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(rows)
+            .map(|(idx, entry)| {
+                let marker = if idx == self.selected { '>' } else { ' ' };
+                let preview = Self::preview(entry, (width as usize).saturating_sub(7));
+                format!("{marker}{idx:>2}: {preview}")
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        let mut popup = PopUp::new("Clipboard History".to_string(), content);
+        popup.set_borders(Borders::ALL);
+        popup.set_title_color(self.color("history_title"));
+        popup.set_content_color(self.color("history_color"));
+        popup.set_border_color(self.color("history_border"));
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        popup.render(popup_area, frame.buffer_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::{ClipboardHistory, IshtarSelectable};
+
+    #[test]
+    fn rendering_on_tiny_areas_does_not_panic() {
+        let mut history = ClipboardHistory::new(Arc::new(HashMap::new()));
+        history.open(vec!["a".to_string(), "b\nc".to_string()]);
+        history.selected = 1;
+        for height in 0..5 {
+            let mut terminal = Terminal::new(TestBackend::new(30, height)).unwrap();
+            terminal
+                .draw(|frame| history.renderize(frame, frame.area()))
+                .unwrap();
+        }
+    }
+}
@@ -10,16 +10,18 @@ use ratatui::{
     Frame,
 };
 
-use crate::helpers::terminal_line::TerminalLine;
+use crate::helpers::{graphemes, terminal_line::TerminalLine};
 
 use super::IshtarSelectable;
 pub struct CommandInterpreter {
     line: TerminalLine,
-    cursor: usize,
+    cursor: usize, //byte offset into the line followed by the requesting buffer
     builtins: HashMap<String, CmdTask>,
     colors: Arc<HashMap<String, u32>>,
     requesting_buffer: String,
     request: CmdTask,
+    showing: bool,   //the content is a message that gets cleared by the next key
+    recording: bool, //a macro is being recorded, so 'q' stops it
 }
 impl CommandInterpreter {
     pub fn new(colors: Arc<HashMap<String, u32>>) -> Self {
@@ -35,25 +37,30 @@ impl CommandInterpreter {
             colors,
             requesting_buffer: String::new(),
             request: CmdTask::Null,
+            showing: false,
+            recording: false,
         }
     }
-    ///Gets the current position of the cursor
+    ///Gets the display column of the cursor
     pub fn cursor(&self) -> usize {
-        self.cursor
+        let content = self.line.to_string() + &self.requesting_buffer;
+        //the command line draws tabs as they are
+        graphemes::display_col(&content, self.cursor.min(content.len()), 0)
     }
     pub fn line(&self) -> &TerminalLine {
         &self.line
     }
     pub fn move_right(&mut self) {
-        if self.line.is_empty() {
+        if self.line.is_empty() || self.is_requesting() {
             return;
         }
-        self.cursor = (self.cursor + 1).min(self.line.len());
+        self.cursor = graphemes::next_grapheme(&self.line.to_string(), self.cursor);
     }
     pub fn move_left(&mut self) {
-        if self.cursor > 0 {
-            self.cursor -= 1;
+        if self.is_requesting() {
+            return;
         }
+        self.cursor = graphemes::prev_grapheme(&self.line.to_string(), self.cursor);
     }
     pub fn is_requesting(&self) -> bool {
         !matches!(self.request, CmdTask::Null)
@@ -63,16 +70,34 @@ impl CommandInterpreter {
         self.set(content);
         self.cursor = content.len();
     }
+    ///Stops the current request without executing it
+    pub fn cancel_request(&mut self) {
+        self.request = CmdTask::Null;
+        self.requesting_buffer.clear();
+        self.clear();
+    }
     ///Sets interpreter content to be the given content
     ///
     pub fn set(&mut self, content: &str) {
         self.line.clear();
         self.line.push_str_back(content);
     }
+    #[inline]
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+    ///Shows the given message until the next key is pressed
+    pub fn show(&mut self, msg: &str) {
+        self.clear();
+        self.set(msg);
+        self.showing = true;
+    }
     fn check_for_unique(&self, c: char) -> CmdTask {
         match c {
             'm' => CmdTask::EnterModify,
             's' => CmdTask::EnterSelection,
+            'b' => CmdTask::EnterBlockSelection,
+            'q' if self.recording => CmdTask::StopMacro,
             _ => CmdTask::Null,
         }
     }
@@ -80,8 +105,12 @@ impl CommandInterpreter {
     ///shortcut, if so returns its task
     pub fn write(&mut self, c: char) -> CmdTask {
         if self.is_requesting() {
+            //confirmations are answered by a single key
+            if matches!(self.request, CmdTask::ReqReplace) {
+                return CmdTask::ReplaceAnswer(c);
+            }
             self.requesting_buffer.push(c);
-            self.cursor += 1;
+            self.cursor += c.len_utf8();
             return CmdTask::Null;
         }
         if self.is_empty() {
@@ -91,22 +120,31 @@ impl CommandInterpreter {
             }
         }
         self.line.insert(self.cursor, c);
-        self.cursor += 1;
+        self.cursor += c.len_utf8();
+        if let Some(query) = self.search_query() {
+            return CmdTask::Search(query);
+        }
         CmdTask::Null
     }
+    ///Gets the query being typed if the interpreter content is a search, which starts with '/'
+    pub fn search_query(&self) -> Option<String> {
+        let content = self.line.to_string();
+        content.strip_prefix('/').map(str::to_string)
+    }
     pub fn backspace(&mut self) {
-        if self.is_requesting() {
-            self.requesting_buffer.pop();
-            self.cursor -= 1;
+        if matches!(self.request, CmdTask::ReqReplace) {
             return;
         }
-        if self.line.is_empty() {
+        if self.is_requesting() {
+            let len = self.requesting_buffer.len();
+            let prev = graphemes::prev_grapheme(&self.requesting_buffer, len);
+            self.requesting_buffer.truncate(prev);
+            self.cursor -= len - prev;
             return;
         }
-        if self.cursor > 0 {
-            self.cursor -= 1;
+        if let Some(removed) = self.line.remove(self.cursor, true) {
+            self.cursor -= removed.len();
         }
-        self.line.remove(self.cursor, true);
     }
     //Clears the interpreter content
     pub fn clear(&mut self) {
@@ -114,17 +152,16 @@ impl CommandInterpreter {
         self.cursor = 0;
     }
     fn execute_internal(&mut self, target: &str) -> CmdTask {
+        if matches!(self.request, CmdTask::ReqReplace) {
+            return CmdTask::Null;
+        }
         if self.is_requesting() {
-            let mut r = CmdTask::Null;
-            match self.request {
-                CmdTask::ReqSaveFile => {
-                    r = CmdTask::SaveFileAs(self.requesting_buffer.clone());
-                }
-                CmdTask::ReqModifyFile => r = CmdTask::ModifyFile(self.requesting_buffer.clone()),
-                _ => {
-                    panic!("must implement extension of{:?}", self.request)
-                }
-            }
+            let r = match self.request {
+                CmdTask::ReqSaveFile => CmdTask::SaveFileAs(self.requesting_buffer.clone()),
+                CmdTask::ReqModifyFile => CmdTask::ModifyFile(self.requesting_buffer.clone()),
+                //the request is dropped so the interpreter does not get stuck on it
+                _ => CmdTask::Warn(format!("Unknown request {:?}", self.request)),
+            };
             self.request = CmdTask::Null;
             self.requesting_buffer.clear();
             self.line.clear();
@@ -134,6 +171,20 @@ impl CommandInterpreter {
         if let Some(builtin) = self.builtins.get(target) {
             return builtin.clone();
         }
+        //Checked before splitting the command as the pattern can contain spaces
+        if let Some(replace) = target
+            .strip_prefix(':')
+            .filter(|t| t.starts_with("%s/") || t.starts_with("s/"))
+        {
+            let task = CmdTask::Replace(replace.to_string());
+            self.clear();
+            return task;
+        }
+        //The search already happened while typing, confirming it goes to the match
+        if target.starts_with('/') {
+            self.clear();
+            return CmdTask::EnterModify;
+        }
         let mut result = CmdTask::Null;
         if let Some('!') = target.chars().nth(0) {
             for cmd in target[1..].split(';') {
@@ -149,16 +200,53 @@ impl CommandInterpreter {
             match broken_cmd[0] {
                 ":s" => result = CmdTask::SaveFileAs(broken_cmd[1].to_string()),
                 ":m" => result = CmdTask::ModifyFile(broken_cmd[1].to_string()),
+                ":re" => result = CmdTask::ReopenWithEncoding(broken_cmd[1].to_string()),
+                ":enc" => result = CmdTask::SetEncoding(broken_cmd[1].to_string()),
+                ":o" => result = parse_offset(broken_cmd[1]),
+                ":yank" => {
+                    //an uppercase register appends to it
+                    let register = broken_cmd[1].to_lowercase();
+                    result = if broken_cmd[1] == register {
+                        parse_register(&register, CmdTask::CopyToRegister)
+                    } else {
+                        parse_register(&register, CmdTask::AppendToRegister)
+                    };
+                }
+                ":put" => result = parse_register(broken_cmd[1], CmdTask::PasteRegister),
+                ":rec" => result = parse_register(broken_cmd[1], CmdTask::RecordMacro),
+                ":play" => {
+                    let times = broken_cmd.get(2).map_or(Some(1), |n| n.parse().ok());
+                    result = match times {
+                        Some(times) => {
+                            parse_register(broken_cmd[1], |r| CmdTask::PlayMacro(r, times))
+                        }
+                        None => CmdTask::Warn(format!("Invalid times {}", broken_cmd[2])),
+                    };
+                }
+                ":savemacro" => match broken_cmd.get(2) {
+                    Some(keybind) => {
+                        result = parse_register(broken_cmd[1], |r| {
+                            CmdTask::SaveMacro(r, keybind.to_string())
+                        })
+                    }
+                    None => result = CmdTask::Warn("Missing the keybind to save to".into()),
+                },
                 _ => {}
             }
         } else {
             match broken_cmd[0] {
                 ":s" => result = CmdTask::SaveFile,
+                ":history" => result = CmdTask::ShowHistory,
                 ":m" => {
                     self.request_data("Set file name ", CmdTask::ReqModifyFile);
                     return result; //make it unable to clear the content
                 }
-                _ => {}
+                //a line number goes to that line
+                cmd => {
+                    if let Some(line) = cmd.strip_prefix(':').and_then(|n| n.parse().ok()) {
+                        result = CmdTask::MoveToLine(line);
+                    }
+                }
             }
         }
         self.clear();
@@ -172,12 +260,31 @@ impl CommandInterpreter {
         self.execute_internal(&self.line.to_string())
     }
     pub fn goto_end(&mut self) {
-        self.cursor = self.line.len() - 1;
+        self.cursor = self.line.len();
     }
     pub fn is_empty(&self) -> bool {
         self.line.len() == 0
     }
 }
+///Reads a byte offset written in decimal, or in hex starting with 0x, into the task going to it
+fn parse_offset(offset: &str) -> CmdTask {
+    let parsed = match offset.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => offset.parse(),
+    };
+    match parsed {
+        Ok(offset) => CmdTask::GotoOffset(offset),
+        Err(_) => CmdTask::Warn(format!("Invalid offset {offset}")),
+    }
+}
+///Reads the register a command targets, a lowercase letter, into the task made from it
+fn parse_register(register: &str, task: impl FnOnce(char) -> CmdTask) -> CmdTask {
+    let mut chars = register.chars();
+    match (chars.next(), chars.next()) {
+        (Some(name), None) if name.is_ascii_lowercase() => task(name),
+        _ => CmdTask::Warn(format!("Invalid register {register}")),
+    }
+}
 impl std::fmt::Display for CommandInterpreter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.line)
@@ -226,17 +333,36 @@ impl IshtarSelectable for CommandInterpreter {
         0
     }
     fn keydown(&mut self, key: ratatui::crossterm::event::KeyCode) -> isht::CmdTask {
+        if self.showing {
+            self.showing = false;
+            self.clear();
+        }
         match key {
+            KeyCode::Esc if matches!(self.request, CmdTask::ReqReplace) => {
+                return CmdTask::ReplaceAnswer('q');
+            }
             KeyCode::Esc => {
+                let searching = self.search_query().is_some();
                 self.clear();
                 self.requesting_buffer.clear();
                 self.request = CmdTask::Null;
+                if searching {
+                    return CmdTask::ClearSearch;
+                }
             }
             KeyCode::Char(c) => return self.write(c),
             KeyCode::Left => self.move_left(),
             KeyCode::Right => self.move_right(),
             KeyCode::Enter => return self.execute(),
-            KeyCode::Backspace => self.backspace(),
+            KeyCode::Backspace => {
+                let searching = self.search_query().is_some();
+                self.backspace();
+                return match self.search_query() {
+                    Some(query) => CmdTask::Search(query),
+                    None if searching => CmdTask::ClearSearch,
+                    None => CmdTask::Null,
+                };
+            }
             _ => return CmdTask::Null,
         };
         CmdTask::Null
@@ -245,3 +371,33 @@ impl IshtarSelectable for CommandInterpreter {
         frame.render_widget(self, area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use isht::CmdTask;
+
+    use super::CommandInterpreter;
+
+    #[test]
+    fn q_stops_a_macro_only_while_recording() {
+        let mut cmd = CommandInterpreter::new(Arc::new(HashMap::new()));
+        assert!(matches!(cmd.write('q'), CmdTask::Null));
+        assert_eq!(cmd.line().to_string(), "q");
+        cmd.clear();
+        cmd.set_recording(true);
+        assert!(matches!(cmd.write('q'), CmdTask::StopMacro));
+    }
+
+    #[test]
+    fn unknown_requests_warn_and_are_dropped() {
+        let mut cmd = CommandInterpreter::new(Arc::new(HashMap::new()));
+        cmd.request_data("Name: ", CmdTask::Exit);
+        assert!(matches!(cmd.execute_internal(""), CmdTask::Warn(_)));
+        assert!(!cmd.is_requesting());
+        cmd.request_data("Name: ", CmdTask::ReqSaveFile);
+        cmd.write('a');
+        assert!(matches!(cmd.execute_internal(""), CmdTask::SaveFileAs(name) if name == "a"));
+    }
+}
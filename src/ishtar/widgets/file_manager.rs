@@ -25,8 +25,6 @@ pub enum ManagingMode {
     Searching,
 }
 pub struct Searcher {
-    preview: bool,
-    orientation: Direction,
     in_dir_paths: Vec<PathBuf>,
     current_idx: usize,
     colors: [Color; 3],
@@ -34,14 +32,12 @@ pub struct Searcher {
     cursor: (usize, usize),
 }
 impl Searcher {
-    pub fn new(orientation: Direction, path: PathBuf, colors: [Color; 3]) -> Self {
+    pub fn new(path: PathBuf, colors: [Color; 3]) -> Self {
         let size = terminal_size();
         Self {
             writing_idx: 0,
             cursor: ((size.0 / 4) as usize + 1, (size.1 / 4 + 1) as usize),
-            preview: false,
             current_idx: 0,
-            orientation,
             in_dir_paths: std::fs::read_dir(&path)
                 .unwrap()
                 .map(|dir| dir.unwrap().path())
@@ -85,7 +81,7 @@ impl Searcher {
         }
     }
 
-    fn all_file_names(&self) -> Vec<(Cow<str>, Cow<str>, usize)> {
+    fn all_file_names(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>, usize)> {
         let mut vec = Vec::with_capacity(self.in_dir_paths.len());
         for (idx, entry) in self.in_dir_paths.iter().enumerate() {
             let parent_name = entry
@@ -100,7 +96,7 @@ impl Searcher {
         vec.push(("..".into(), "".into(), vec.len()));
         vec
     }
-    fn file_names_from(&self, n: usize) -> Vec<(Cow<str>, Cow<str>, usize)> {
+    fn file_names_from(&self, n: usize) -> Vec<(Cow<'_, str>, Cow<'_, str>, usize)> {
         let mut vec = Vec::with_capacity(self.in_dir_paths.len());
         for (idx, entry) in self.in_dir_paths[n..].iter().enumerate() {
             let parent_name = entry
@@ -115,7 +111,7 @@ impl Searcher {
         vec.push(("..".into(), "".into(), vec.len()));
         vec
     }
-    fn file_names(&self, range: Range<usize>) -> Vec<(Cow<str>, Cow<str>, usize)> {
+    fn file_names(&self, range: Range<usize>) -> Vec<(Cow<'_, str>, Cow<'_, str>, usize)> {
         let mut vec = Vec::with_capacity(self.in_dir_paths.len());
         for (idx, entry) in self.in_dir_paths[range].iter().enumerate() {
             let parent_name = entry
@@ -235,7 +231,6 @@ impl Searcher {
                 )
                 .wrap(Wrap { trim: true })
                 .render(rect, buf);
-            let now = std::time::Instant::now();
             loop {
                 fx.process(Duration::from_millis(33), buf, rect);
             }
@@ -243,7 +238,6 @@ impl Searcher {
     }
 }
 pub struct FileManager {
-    seeing_file: bool, //Flag for checking if file content is being shown while searching
     pub mode: ManagingMode,
     opened: bool,
     buffer: String,
@@ -272,19 +266,9 @@ impl FileManager {
             _ => todo!(),
         }
     }
-    pub fn new_horizontal(see_file: bool, path: PathBuf, colors: IshtarColors) -> Self {
+    pub fn new(path: PathBuf, colors: IshtarColors) -> Self {
         Self {
-            searcher: Searcher::new(Direction::Horizontal, path, Self::get_colors_from(colors)),
-            seeing_file: see_file,
-            mode: ManagingMode::Searching,
-            opened: false,
-            buffer: String::with_capacity(32),
-        }
-    }
-    pub fn new_vertical(see_file: bool, path: PathBuf, colors: IshtarColors) -> Self {
-        Self {
-            searcher: Searcher::new(Direction::Vertical, path, Self::get_colors_from(colors)),
-            seeing_file: see_file,
+            searcher: Searcher::new(path, Self::get_colors_from(colors)),
             mode: ManagingMode::Searching,
             opened: false,
             buffer: String::with_capacity(32),
@@ -74,6 +74,11 @@ impl KeybindHandler {
         self.bindings[mode].get(val)
     }
 
+    ///Binds the given tasks to the given keybind on the given mode, replacing the ones it had
+    pub fn bind(&mut self, val: String, mode: usize, tasks: Vec<ConfigStatment>) {
+        self.bindings[mode].insert(val, tasks);
+    }
+
     pub fn content(&self) -> String {
         self.buffer
             .join("-")
@@ -16,6 +16,7 @@ pub trait IshtarSelectable: Downcast {
 impl_downcast!(IshtarSelectable);
 
 pub mod clipboard;
+pub mod clipboard_history;
 pub mod command_interpreter;
 pub mod file_manager;
 pub mod keybind_handler;
@@ -0,0 +1,131 @@
+use std::ops::Range;
+
+use isht::CmdTask;
+
+use crate::helpers::{graphemes, min_max};
+
+use super::{Selection, TextArea, TextAreaMode};
+
+impl TextArea {
+    ///Starts selecting a rectangle of text, from the cursor to where it moves
+    pub fn enter_block_selection(&mut self) {
+        self.history.seal();
+        self.confirm_search();
+        self.mode = TextAreaMode::BlockSelecting;
+        self.selection_anchor = (self.y, self.x);
+        self.cursors.clear();
+    }
+    pub fn is_block_selecting(&self) -> bool {
+        matches!(self.mode, TextAreaMode::BlockSelecting)
+    }
+    ///Gets the display columns the block covers, from the column of the anchor to the one of the
+    ///cursor. The last column is not included
+    fn block_columns(&self) -> Range<usize> {
+        let anchor = self
+            .document
+            .display_col(self.selection_anchor, self.tab_width);
+        let cursor = self.document.display_col((self.y, self.x), self.tab_width);
+        let (left, right) = min_max(anchor, cursor);
+        left..right
+    }
+    ///Gets the bytes of the line at the given index that are inside the block, if the line is
+    ///one of its rows
+    pub(super) fn block_on_line(&self, idx: usize) -> Option<Range<usize>> {
+        let (top, bottom) = min_max(self.selection_anchor.0, self.y);
+        if !self.is_block_selecting() || idx < top || idx > bottom {
+            return None;
+        }
+        let columns = self.block_columns();
+        let line = self.document.line_str(idx);
+        let start = graphemes::byte_at_col(&line, columns.start, self.tab_width);
+        let end = graphemes::byte_at_col(&line, columns.end, self.tab_width);
+        Some(start..end)
+    }
+    ///Gets how many blank columns are drawn after the line at the given index, shown from the
+    ///given column, for the block to reach its last column on it. Given as (before the block,
+    ///inside it)
+    pub(super) fn block_padding(
+        &self,
+        idx: usize,
+        line: &str,
+        from: usize,
+    ) -> Option<(usize, usize)> {
+        self.block_on_line(idx)?;
+        let columns = self.block_columns();
+        let width = graphemes::display_width(line, self.tab_width).max(from);
+        if width >= columns.end {
+            return None;
+        }
+        let before = columns.start.saturating_sub(width);
+        Some((before, columns.end - width.max(columns.start)))
+    }
+    ///Gets the part of each row of the block ordered as (start, end), the one on the row of the
+    ///cursor first. Rows hidden by folds are skipped
+    pub(super) fn block_ranges(&self) -> Option<Vec<Selection>> {
+        if !self.is_block_selecting() {
+            return None;
+        }
+        let (top, bottom) = min_max(self.selection_anchor.0, self.y);
+        let row = |idx| {
+            let range = self.block_on_line(idx)?;
+            Some(((idx, range.start), (idx, range.end)))
+        };
+        let others = (top..=bottom).filter(|idx| *idx != self.y && !self.is_hidden(*idx));
+        std::iter::once(self.y).chain(others).map(row).collect()
+    }
+    ///Turns the rows of the block into a selection each, the one on the row of the cursor being
+    ///the main one
+    pub(super) fn block_to_cursors(&mut self) {
+        let Some(ranges) = self.block_ranges() else {
+            return;
+        };
+        let mut ranges = ranges.into_iter();
+        if let Some((start, end)) = ranges.next() {
+            self.selection_anchor = start;
+            self.set_cursor(end);
+        }
+        self.cursors = ranges.collect();
+        self.mode = TextAreaMode::Selecting;
+    }
+    ///Writes the given char in place of every row of the block, leaving a cursor on each row so
+    ///the next ones are written there too
+    pub fn write_into_block(&mut self, c: char) -> CmdTask {
+        self.delete_selection();
+        self.write_char(c);
+        CmdTask::EnterModify
+    }
+    ///Pastes the given content over the block. Content with multiple lines is dropped in as a
+    ///column, a line per row from the top of the block on, padding the rows too short to reach
+    ///it. A single line replaces every row
+    pub(super) fn paste_into_block(&mut self, content: &str) -> CmdTask {
+        if !content.contains('\n') {
+            self.block_to_cursors();
+            return self.paste(content);
+        }
+        let left = self.block_columns().start;
+        let top = self.selection_anchor.0.min(self.y);
+        self.history.begin_group();
+        self.delete_selection();
+        self.cursors.clear();
+        let mut end = (top, 0);
+        for (row, text) in (top..).zip(content.lines()) {
+            let len_lines = self.document.len_lines();
+            if row == len_lines {
+                let last = (len_lines - 1, self.document.line_len(len_lines - 1));
+                self.insert_at(last, "\n");
+            }
+            let line = self.document.line_string(row);
+            let width = graphemes::display_width(&line, self.tab_width);
+            let at = if width < left {
+                let padding = " ".repeat(left - width);
+                self.insert_at((row, line.len()), &padding)
+            } else {
+                (row, graphemes::byte_at_col(&line, left, self.tab_width))
+            };
+            end = self.insert_at(at, text);
+        }
+        self.history.end_group();
+        self.set_cursor(end);
+        CmdTask::EnterModify
+    }
+}
@@ -0,0 +1,213 @@
+use std::ops::Range;
+
+use crate::helpers::history::BufferPos;
+
+use super::TextArea;
+
+///Brackets as (opener, closer)
+pub(super) const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+///Lines past the visible ones the text is scanned through for the bracket paired with the one
+///under the cursor while rendering
+pub(super) const SCAN_MARGIN: usize = 200;
+
+///Gets the bracket paired with the given one and if the given one opens the pair
+fn partner(c: char) -> Option<(char, bool)> {
+    BRACKETS.iter().find_map(|&(open, close)| {
+        if c == open {
+            Some((close, true))
+        } else if c == close {
+            Some((open, false))
+        } else {
+            None
+        }
+    })
+}
+
+impl TextArea {
+    ///Gets the char at the given position, if it is not the end of the line
+    fn char_at(&self, (y, x): BufferPos) -> Option<char> {
+        self.document.line_str(y)[x..].chars().next()
+    }
+    ///Gets the char before the given position, if it is not the init of the line
+    fn char_before(&self, (y, x): BufferPos) -> Option<char> {
+        self.document.line_str(y)[..x].chars().next_back()
+    }
+    ///Finds the byte of the bracket paired with the one at the given byte. With a syntax tree,
+    ///bracket tokens pair with their siblings and the brackets inside strings and comments are
+    ///skipped. Brackets the grammar does not parse are paired through the text of the given lines
+    fn matching_bracket(&self, byte: usize, c: char, lines: Range<usize>) -> Option<usize> {
+        let (other, opens) = partner(c)?;
+        if let Some(syntax) = &self.syntax {
+            let mut syntax = syntax.borrow_mut();
+            let tree = syntax.tree(&self.document)?;
+            let token = tree.root_node().descendant_for_byte_range(byte, byte + 1)?;
+            let (kind, other_kind) = (c.to_string(), other.to_string());
+            if token.byte_range() != (byte..byte + 1) || token.kind() != kind {
+                //the grammar may not parse the text, like the paragraphs of markdown
+                let mut node = Some(token);
+                while let Some(current) = node {
+                    if ["string", "comment"]
+                        .iter()
+                        .any(|k| current.kind().contains(k))
+                    {
+                        return None;
+                    }
+                    node = current.parent();
+                }
+                return self.scan_bracket(byte, (c, other), opens, lines);
+            }
+            let mut depth = 0;
+            let mut node = token;
+            loop {
+                node = if opens {
+                    node.next_sibling()
+                } else {
+                    node.prev_sibling()
+                }?;
+                if node.kind() == kind {
+                    depth += 1;
+                } else if node.kind() == other_kind {
+                    if depth == 0 {
+                        return Some(node.start_byte());
+                    }
+                    depth -= 1;
+                }
+            }
+        }
+        self.scan_bracket(byte, (c, other), opens, lines)
+    }
+    ///Finds the byte of the bracket paired with the given one at the given byte through the text
+    ///of the given lines
+    fn scan_bracket(
+        &self,
+        byte: usize,
+        (c, other): (char, char),
+        opens: bool,
+        lines: Range<usize>,
+    ) -> Option<usize> {
+        let rope = self.document.rope();
+        let char_idx = rope.byte_to_char(byte);
+        let end = rope.line_to_char(lines.end.min(rope.len_lines()));
+        let start = rope.line_to_char(lines.start.min(rope.len_lines()));
+        let mut depth = 0;
+        let mut check = |idx: usize, current: char| {
+            if current == c {
+                depth += 1;
+            } else if current == other {
+                if depth == 0 {
+                    return Some(rope.char_to_byte(idx));
+                }
+                depth -= 1;
+            }
+            None
+        };
+        if opens {
+            (char_idx + 1..end)
+                .zip(rope.chars_at(char_idx + 1))
+                .find_map(|(idx, current)| check(idx, current))
+        } else {
+            (start..char_idx)
+                .rev()
+                .zip(rope.chars_at(char_idx).reversed())
+                .find_map(|(idx, current)| check(idx, current))
+        }
+    }
+    ///Gets the positions of the bracket under the cursor, or else the one right before it, and of
+    ///the bracket paired with it. Brackets paired through the text are only looked for inside the
+    ///given lines
+    pub fn bracket_pair(&self, lines: Range<usize>) -> Option<(BufferPos, BufferPos)> {
+        let cursor = (self.y, self.x);
+        let before = self
+            .char_before(cursor)
+            .map(|c| ((self.y, self.x - c.len_utf8()), c));
+        let (pos, c) = self
+            .char_at(cursor)
+            .filter(|c| partner(*c).is_some())
+            .map(|c| (cursor, c))
+            .or(before.filter(|(_, c)| partner(*c).is_some()))?;
+        let other = self.matching_bracket(self.document.pos_to_byte(pos), c, lines)?;
+        Some((pos, self.document.byte_to_pos(other)))
+    }
+    ///Moves the cursor to the bracket paired with the one under it. Returns if there was one
+    pub fn match_bracket(&mut self) -> bool {
+        match self.bracket_pair(0..self.document.len_lines()) {
+            Some((_, other)) => {
+                self.set_cursor(other);
+                true
+            }
+            None => false,
+        }
+    }
+    ///Writes the given punctuation char. With auto pairs, an opening bracket or quote also writes
+    ///its closer after the cursor, and typing a closer written that way steps past it
+    pub(super) fn write_punctuation(&mut self, c: char) {
+        let cursor = (self.y, self.x);
+        if self.auto_closers.contains(&cursor) && self.char_at(cursor) == Some(c) {
+            self.auto_closers.retain(|pos| *pos != cursor);
+            self.set_cursor((self.y, self.x + c.len_utf8()));
+            return;
+        }
+        let closer = match partner(c) {
+            Some((closer, true)) => Some(closer),
+            _ if QUOTES.contains(&c) => Some(c),
+            _ => None,
+        };
+        let next = self.char_at(cursor);
+        //pairs are only opened before blanks or closers, and quotes not right after a word
+        let free = next.is_none_or(|n| n.is_whitespace() || ")]},;:".contains(n));
+        let after_word = self
+            .char_before(cursor)
+            .is_some_and(|p| p.is_alphanumeric() || p == c);
+        match closer {
+            Some(closer) if self.auto_pairs && free && !(closer == c && after_word) => {
+                let pair = format!("{c}{closer}");
+                self.insert_at(cursor, &pair);
+                let inside = (self.y, self.x + c.len_utf8());
+                self.auto_closers.push(inside);
+                self.set_cursor(inside);
+            }
+            _ => self.move_after_insert(c),
+        }
+    }
+    ///Checks if the cursor is between an empty pair of brackets or quotes, which backspace
+    ///removes whole with auto pairs
+    pub(super) fn inside_empty_pair(&self) -> bool {
+        let cursor = (self.y, self.x);
+        let (Some(before), Some(at)) = (self.char_before(cursor), self.char_at(cursor)) else {
+            return false;
+        };
+        self.auto_pairs
+            && (partner(before) == Some((at, true)) || (QUOTES.contains(&before) && before == at))
+    }
+    ///Checks if the cursor is between an empty pair of brackets, which a newline opens up with
+    ///auto pairs
+    pub(super) fn inside_empty_brackets(&self) -> bool {
+        self.inside_empty_pair()
+            && !self
+                .char_at((self.y, self.x))
+                .is_some_and(|c| QUOTES.contains(&c))
+    }
+    ///Moves the closers written by auto pairs after an edit that replaced the text between
+    ///`start` and `old_end` by the one between `start` and `new_end`. The ones removed are
+    ///forgotten
+    pub(super) fn shift_auto_closers(
+        &mut self,
+        start: BufferPos,
+        old_end: BufferPos,
+        new_end: BufferPos,
+    ) {
+        self.auto_closers
+            .retain(|pos| *pos < start || *pos >= old_end);
+        for pos in &mut self.auto_closers {
+            if *pos < old_end {
+                continue;
+            }
+            *pos = if pos.0 == old_end.0 {
+                (new_end.0, new_end.1 + pos.1 - old_end.1)
+            } else {
+                (pos.0 + new_end.0 - old_end.0, pos.1)
+            };
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::helpers::{history::BufferPos, min_max};
+
+use super::{Selection, TextArea};
+
+///Moves the given position after an edit that replaced the text between `start` and `old_end` by
+///the one between `start` and `new_end`. Positions inside the replaced text go to its start
+fn shift_pos(
+    pos: BufferPos,
+    start: BufferPos,
+    old_end: BufferPos,
+    new_end: BufferPos,
+) -> BufferPos {
+    if pos < start {
+        pos
+    } else if pos < old_end {
+        start
+    } else if pos.0 == old_end.0 {
+        (new_end.0, new_end.1 + pos.1 - old_end.1)
+    } else {
+        (pos.0 + new_end.0 - old_end.0, pos.1)
+    }
+}
+
+impl TextArea {
+    ///Gets how many cursors the buffer has, the main one included
+    pub fn cursor_count(&self) -> usize {
+        self.cursors.len() + 1
+    }
+    ///Gets the ranges covered by every cursor ordered as (start, end), the one of the main cursor
+    ///first. Outside selection mode, each range is empty at its cursor
+    pub(super) fn cursor_ranges(&self) -> Vec<Selection> {
+        let main = (self.y, self.x);
+        let main = self.selection_range().unwrap_or((main, main));
+        let extras = self.cursors.iter().map(|&(anchor, cursor)| {
+            if self.is_selecting() {
+                min_max(anchor, cursor)
+            } else {
+                (cursor, cursor)
+            }
+        });
+        std::iter::once(main).chain(extras).collect()
+    }
+    ///Gets the selections of the cursors other than the main one, ordered as (start, end)
+    pub(super) fn extra_selections(&self) -> impl Iterator<Item = Selection> + '_ {
+        self.cursors
+            .iter()
+            .filter(|_| self.is_selecting())
+            .map(|&(anchor, cursor)| min_max(anchor, cursor))
+    }
+    ///Gets the positions of the cursors other than the main one
+    pub(super) fn extra_cursors(&self) -> impl Iterator<Item = BufferPos> + '_ {
+        self.cursors.iter().map(|(_, cursor)| *cursor)
+    }
+    ///Runs the given action at every cursor as if each one was the main cursor, the main one first.
+    ///The edits made along the way are a single undo unit and move the other cursors with the text.
+    ///The hex and large file views only have the main cursor
+    pub fn at_every_cursor(&mut self, mut action: impl FnMut(&mut Self)) {
+        if self.cursors.is_empty() || self.hex.is_some() || self.large.is_some() {
+            action(self);
+            return;
+        }
+        self.history.begin_group();
+        //the main cursor is kept with the others so their edits move it too
+        self.cursors
+            .insert(0, (self.selection_anchor, (self.y, self.x)));
+        for idx in 0..self.cursors.len() {
+            let (anchor, (y, x)) = self.cursors[idx];
+            self.selection_anchor = anchor;
+            (self.y, self.x) = (y, x);
+            action(self);
+            self.cursors[idx] = (self.selection_anchor, (self.y, self.x));
+        }
+        let (anchor, (y, x)) = self.cursors.remove(0);
+        self.selection_anchor = anchor;
+        self.set_cursor((y, x));
+        self.history.end_group();
+        self.merge_cursors();
+    }
+    ///Forgets the cursors that ended up at the same place as another one
+    pub(super) fn merge_cursors(&mut self) {
+        let mut seen = vec![(self.y, self.x)];
+        self.cursors.retain(|(_, cursor)| {
+            let repeated = seen.contains(cursor);
+            seen.push(*cursor);
+            !repeated
+        });
+    }
+    ///Keeps only the main cursor. Returns if there were others
+    pub fn clear_cursors(&mut self) -> bool {
+        let had = !self.cursors.is_empty();
+        self.cursors.clear();
+        had
+    }
+    ///Adds a cursor on the line below the lowest cursor, at the column of the main one, and makes
+    ///it the main one. Returns if there was a line below
+    pub fn add_cursor_below(&mut self) -> bool {
+        let lowest = self.extra_cursors().chain([(self.y, self.x)]).max();
+        let line = lowest.and_then(|(y, _)| self.next_visible_line(y));
+        self.add_cursor_on(line)
+    }
+    ///Adds a cursor on the line above the highest cursor, at the column of the main one, and
+    ///makes it the main one. Returns if there was a line above
+    pub fn add_cursor_above(&mut self) -> bool {
+        let highest = self.extra_cursors().chain([(self.y, self.x)]).min();
+        let line = highest.and_then(|(y, _)| Some(self.visible_line(y.checked_sub(1)?)));
+        self.add_cursor_on(line)
+    }
+    ///Adds a cursor on the given line, at the column of the main one, and makes it the main one
+    fn add_cursor_on(&mut self, line: Option<usize>) -> bool {
+        let Some(line) = line else {
+            return false;
+        };
+        let col = self.document.display_col((self.y, self.x), self.tab_width);
+        let x = self.document.byte_at_col(line, col, self.tab_width);
+        self.cursors.push((self.selection_anchor, (self.y, self.x)));
+        self.selection_anchor = (line, x);
+        self.set_cursor((line, x));
+        true
+    }
+    ///Selects the word under the cursor when nothing is selected. Otherwise adds a selection on the
+    ///next occurrence of the selected text not selected yet, going around the end of the buffer,
+    ///and makes it the main one. Returns if something got selected
+    pub fn select_next_occurrence(&mut self) -> bool {
+        let cursor = (self.y, self.x);
+        let (start, end) = self.selection_range().unwrap_or((cursor, cursor));
+        if start == end {
+            let Some(word) = self.word_at(cursor) else {
+                return false;
+            };
+            self.selection_anchor = (self.y, word.start);
+            self.set_cursor((self.y, word.end));
+            return true;
+        }
+        let text = self.text_between(start, end);
+        let taken: Vec<usize> = self
+            .cursor_ranges()
+            .into_iter()
+            .map(|(start, _)| self.document.pos_to_byte(start))
+            .collect();
+        let from = self.document.pos_to_byte(end);
+        let step = text.chars().next().map_or(1, char::len_utf8);
+        let (mut at, mut wrapped) = (from, false);
+        let found = loop {
+            match self.document.find_from(&text, at) {
+                Some(found) if wrapped && found >= from => return false,
+                Some(found) if taken.contains(&found) => at = found + step,
+                Some(found) => break found,
+                None if !wrapped => (at, wrapped) = (0, true),
+                None => return false,
+            }
+        };
+        self.cursors.push((self.selection_anchor, cursor));
+        self.selection_anchor = self.document.byte_to_pos(found);
+        self.set_cursor(self.document.byte_to_pos(found + text.len()));
+        true
+    }
+    ///Splits the selection into one selection per line it covers, the one on the last line being
+    ///the main one. Lines hidden by folds are skipped. Returns if there was a selection
+    pub fn split_selection(&mut self) -> bool {
+        let Some((start, end)) = self.selection_range() else {
+            return false;
+        };
+        //a selection finishing at the init of a line does not cover it
+        let last = if end.1 == 0 && end.0 > start.0 {
+            end.0 - 1
+        } else {
+            end.0
+        };
+        let mut lines: Vec<Selection> = (start.0..=last)
+            .filter(|idx| !self.is_hidden(*idx))
+            .map(|idx| {
+                let from = if idx == start.0 { start.1 } else { 0 };
+                let to = if idx == end.0 {
+                    end.1
+                } else {
+                    self.line_end(idx)
+                };
+                ((idx, from), (idx, to))
+            })
+            .collect();
+        let Some((anchor, cursor)) = lines.pop() else {
+            return false;
+        };
+        self.cursors.extend(lines);
+        self.selection_anchor = anchor;
+        self.set_cursor(cursor);
+        self.merge_cursors();
+        true
+    }
+    ///Moves the cursors other than the main one after an edit that replaced the text between
+    ///`start` and `old_end` by the one between `start` and `new_end`
+    pub(super) fn shift_cursors(
+        &mut self,
+        start: BufferPos,
+        old_end: BufferPos,
+        new_end: BufferPos,
+    ) {
+        for (anchor, cursor) in &mut self.cursors {
+            *anchor = shift_pos(*anchor, start, old_end, new_end);
+            *cursor = shift_pos(*cursor, start, old_end, new_end);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::ops::Range;
+
+use super::TextArea;
+
+impl TextArea {
+    ///Gets the byte range of the smallest syntax node that is bigger than and contains the given
+    ///range. Without a syntax tree, the word, the line and then the whole buffer are used instead
+    fn enclosing_range(&mut self, range: Range<usize>) -> Option<Range<usize>> {
+        let document = &self.document;
+        if let Some(tree) = self
+            .syntax
+            .as_mut()
+            .and_then(|s| s.get_mut().tree(document))
+        {
+            let mut node = tree
+                .root_node()
+                .descendant_for_byte_range(range.start, range.end)?;
+            while node.start_byte() >= range.start && node.end_byte() <= range.end {
+                node = node.parent()?;
+            }
+            return Some(node.byte_range());
+        }
+        let (y, x) = (self.y, self.x);
+        let line_init = self.document.line_to_byte(y);
+        let word = self
+            .word_at((y, x))
+            .map(|word| line_init + word.start..line_init + word.end);
+        let line = line_init..line_init + self.document.line_len(y);
+        [word, Some(line), Some(0..self.document.len_bytes())]
+            .into_iter()
+            .flatten()
+            .find(|r| r.start <= range.start && r.end >= range.end && r.len() > range.len())
+    }
+    ///Grows the selection to the syntax node around it, selecting from where the cursor is when
+    ///nothing is selected yet. Returns if the selection changed
+    pub fn expand_selection(&mut self) -> bool {
+        let cursor = (self.y, self.x);
+        let current = if self.is_selecting() {
+            (self.selection_anchor, cursor)
+        } else {
+            (cursor, cursor)
+        };
+        if self
+            .expansions
+            .last()
+            .is_some_and(|(_, after)| *after != current)
+        {
+            self.expansions.clear();
+        }
+        let (start, end) = self.selection_range().unwrap_or((cursor, cursor));
+        let range = self.document.pos_to_byte(start)..self.document.pos_to_byte(end);
+        let Some(expanded) = self.enclosing_range(range) else {
+            return false;
+        };
+        if !self.is_selecting() {
+            self.enter_selection();
+        }
+        self.selection_anchor = self.document.byte_to_pos(expanded.start);
+        self.set_cursor(self.document.byte_to_pos(expanded.end));
+        self.expansions
+            .push((current, (self.selection_anchor, (self.y, self.x))));
+        true
+    }
+    ///Goes back to the selection there was before the last expansion. Returns if there was one
+    pub fn shrink_selection(&mut self) -> bool {
+        let current = (self.selection_anchor, (self.y, self.x));
+        match self.expansions.pop() {
+            Some((before, after)) if after == current => {
+                self.selection_anchor = before.0;
+                self.set_cursor(before.1);
+                true
+            }
+            _ => {
+                self.expansions.clear();
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::ops::Range;
+
+use crate::helpers::history::BufferPos;
+
+use super::TextArea;
+
+///A fold as the (first, last) lines it covers. The first line stays visible when it is closed
+pub type Fold = (usize, usize);
+
+impl TextArea {
+    ///Gets the amount of whitespace chars the line at the given index starts with. Blank lines
+    ///have no indentation
+    fn indentation(&self, idx: usize) -> Option<usize> {
+        let line = self.document.line(idx);
+        let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+        (indent < line.len_chars()).then_some(indent)
+    }
+    ///Gets the fold starting at the given line based on the indentation, which covers every line
+    ///after it that is more indented
+    fn indentation_fold(&self, idx: usize) -> Option<Fold> {
+        let base = self.indentation(idx)?;
+        let mut end = idx;
+        for next in idx + 1..self.document.len_lines() {
+            match self.indentation(next) {
+                None => continue,
+                Some(indent) if indent > base => end = next,
+                Some(_) => break,
+            }
+        }
+        (end > idx).then_some((idx, end))
+    }
+    ///Gets the fold of the biggest syntax node starting at the given line that finishes in a
+    ///line after it
+    fn syntax_fold(&mut self, idx: usize) -> Option<Option<Fold>> {
+        let line = self.document.line_string(idx);
+        let init = self.document.line_to_byte(idx);
+        let edges = [
+            line.char_indices().find(|(_, c)| !c.is_whitespace()),
+            line.char_indices().rfind(|(_, c)| !c.is_whitespace()),
+        ];
+        let document = &self.document;
+        let tree = self.syntax.as_mut()?.get_mut().tree(document)?;
+        let root = tree.root_node();
+        let mut fold = None;
+        for (col, c) in edges.into_iter().flatten() {
+            let Some(mut node) =
+                root.descendant_for_byte_range(init + col, init + col + c.len_utf8())
+            else {
+                continue;
+            };
+            while node.id() != root.id() && node.start_position().row == idx {
+                let end = node.end_position();
+                //a node finishing at the init of a line ends on the line before
+                let last = if end.column == 0 {
+                    end.row - 1
+                } else {
+                    end.row
+                };
+                if last > fold.map_or(idx, |(_, e)| e) {
+                    fold = Some((idx, last));
+                }
+                let Some(parent) = node.parent() else { break };
+                node = parent;
+            }
+        }
+        Some(fold)
+    }
+    ///Gets the fold starting at the given line. Uses the syntax tree if there is one, the
+    ///indentation otherwise
+    pub fn fold_at(&mut self, idx: usize) -> Option<Fold> {
+        match self.syntax_fold(idx) {
+            Some(fold) => fold,
+            None => self.indentation_fold(idx),
+        }
+    }
+    ///Gets the innermost fold that contains the given line
+    fn enclosing_fold(&mut self, idx: usize) -> Option<Fold> {
+        (0..=idx)
+            .rev()
+            .filter_map(|start| self.fold_at(start))
+            .find(|(_, end)| *end >= idx)
+    }
+    ///Closes the fold around the cursor, moving the cursor to its first line. Returns if there was
+    ///one
+    pub fn fold(&mut self) -> bool {
+        let Some(fold) = self.enclosing_fold(self.y) else {
+            return false;
+        };
+        let idx = self.folds.partition_point(|f| *f < fold);
+        if self.folds.get(idx) != Some(&fold) {
+            self.folds.insert(idx, fold);
+        }
+        if self.y != fold.0 {
+            self.set_cursor((fold.0, 0));
+        }
+        true
+    }
+    ///Opens the closed folds starting at the line of the cursor. Returns if there was one
+    pub fn unfold(&mut self) -> bool {
+        let len = self.folds.len();
+        self.folds.retain(|(start, _)| *start != self.y);
+        len != self.folds.len()
+    }
+    ///Opens every fold if there is a closed one, closes every fold otherwise
+    pub fn toggle_all_folds(&mut self) {
+        if !self.folds.is_empty() {
+            self.folds.clear();
+            return;
+        }
+        let mut idx = 0;
+        while idx < self.document.len_lines() {
+            match self.fold_at(idx) {
+                Some(fold) => {
+                    self.folds.push(fold);
+                    idx = fold.1 + 1;
+                }
+                None => idx += 1,
+            }
+        }
+        self.y = self.visible_line(self.y);
+        self.x = self.x.min(self.line_end(self.y));
+    }
+    ///Gets the closed fold starting at the given line, if the line is visible
+    pub fn closed_fold_at(&self, idx: usize) -> Option<Fold> {
+        if self.is_hidden(idx) {
+            return None;
+        }
+        self.folds
+            .iter()
+            .filter(|(start, _)| *start == idx)
+            .max_by_key(|(_, end)| *end)
+            .copied()
+    }
+    ///Gets the ranges of lines hidden by closed folds, sorted and merged
+    fn hidden_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for (start, end) in &self.folds {
+            match ranges.last_mut() {
+                Some(last) if last.end > *start => last.end = last.end.max(end + 1),
+                _ => ranges.push(start + 1..end + 1),
+            }
+        }
+        ranges
+    }
+    ///Checks if the line at the given index is inside a closed fold
+    pub fn is_hidden(&self, idx: usize) -> bool {
+        self.folds
+            .iter()
+            .any(|(start, end)| *start < idx && idx <= *end)
+    }
+    ///Gets the visible line that shows the given line, which is the line itself or the first line
+    ///of the closed fold hiding it
+    pub fn visible_line(&self, idx: usize) -> usize {
+        match self.hidden_ranges().into_iter().find(|r| r.contains(&idx)) {
+            Some(range) => range.start - 1,
+            None => idx,
+        }
+    }
+    ///Gets the first line shown after the given one, if there is one
+    pub fn next_visible_line(&self, idx: usize) -> Option<usize> {
+        let next = match self
+            .hidden_ranges()
+            .into_iter()
+            .find(|r| r.contains(&(idx + 1)))
+        {
+            Some(range) => range.end,
+            None => idx + 1,
+        };
+        (next < self.document.len_lines()).then_some(next)
+    }
+    ///Gets the row the given line is shown at when every closed fold is a single line
+    pub fn visual_row(&self, idx: usize) -> usize {
+        let idx = self.visible_line(idx);
+        let hidden: usize = self
+            .hidden_ranges()
+            .into_iter()
+            .take_while(|r| r.start < idx)
+            .map(|r| r.len())
+            .sum();
+        idx - hidden
+    }
+    ///Gets the line shown at the given row when every closed fold is a single line
+    pub fn line_at_row(&self, row: usize) -> usize {
+        let mut idx = row;
+        for range in self.hidden_ranges() {
+            if range.start > idx {
+                break;
+            }
+            idx += range.len();
+        }
+        idx.min(self.document.len_lines() - 1)
+    }
+    ///Opens the folds hiding the given line
+    pub(super) fn reveal(&mut self, idx: usize) {
+        self.folds
+            .retain(|(start, end)| !(*start < idx && idx <= *end));
+    }
+    ///Moves the folds after an edit that replaced the text between `start` and `old_end` by the
+    ///one between `start` and `new_end`. Folds partially covered by the edit are opened
+    pub(super) fn shift_folds(&mut self, start: BufferPos, old_end: BufferPos, new_end: BufferPos) {
+        let (first, old_last) = (start.0, old_end.0);
+        if old_last == new_end.0 {
+            return;
+        }
+        let delta = new_end.0 as isize - old_last as isize;
+        let shift = |line: usize| (line as isize + delta) as usize;
+        self.folds = self
+            .folds
+            .iter()
+            .filter_map(|&(start, end)| {
+                //edits finishing at the init of the first line move the whole fold
+                if start > old_last || old_end == (start, 0) {
+                    Some((shift(start), shift(end)))
+                } else if end < first {
+                    Some((start, end))
+                } else if start <= first && end >= old_last && shift(end) > start {
+                    Some((start, shift(end)))
+                } else {
+                    None
+                }
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,402 @@
+use std::ops::Range;
+
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::KeyCode,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+use crate::helpers::{
+    file_format::{encoding_for_label, EncodingError, FileFormat},
+    history::{BufferPos, EditHistory},
+};
+
+use super::TextArea;
+
+const OFFSET_COLS: usize = 10; //8 hex digits of the offset and 2 spaces
+const MAX_ROW_BYTES: usize = 16;
+
+///The bytes of a binary file, shown as hex and edited by overwriting their nibbles
+#[derive(Debug, Default)]
+pub struct HexView {
+    bytes: Vec<u8>,
+    cursor: usize,                //byte under the cursor
+    low: bool,                    //the cursor is on the second nibble of the byte
+    pattern: Option<Vec<u8>>,     //bytes being searched
+    search_origin: Option<usize>, //where the cursor was when the search started being typed
+    text: Option<HiddenText>,     //the text the buffer was switched from
+}
+
+///The text of a buffer switched to hex, kept to go back to it without losing its history
+#[derive(Debug)]
+struct HiddenText {
+    content: String,
+    history: EditHistory,
+    cursor: BufferPos,
+}
+
+impl HexView {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            ..Default::default()
+        }
+    }
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+    #[inline]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+    ///Overwrites the nibble under the cursor with the given hex digit and goes to the next one.
+    ///Returns false if the char is not a hex digit
+    pub fn write_nibble(&mut self, c: char) -> bool {
+        let (Some(digit), Some(byte)) = (c.to_digit(16), self.bytes.get_mut(self.cursor)) else {
+            return false;
+        };
+        let digit = digit as u8;
+        if self.low {
+            *byte = (*byte & 0xf0) | digit;
+            if self.cursor + 1 < self.bytes.len() {
+                self.goto(self.cursor + 1);
+            }
+        } else {
+            *byte = (*byte & 0x0f) | (digit << 4);
+            self.low = true;
+        }
+        true
+    }
+    ///Moves the cursor to the first nibble of the byte at the given offset. Returns false if
+    ///there is no byte there
+    pub fn goto(&mut self, offset: usize) -> bool {
+        if offset >= self.bytes.len() {
+            return false;
+        }
+        self.cursor = offset;
+        self.low = false;
+        true
+    }
+    ///Moves the cursor a nibble to the left or to the right
+    fn move_nibble(&mut self, right: bool) {
+        match (right, self.low) {
+            (true, false) if !self.bytes.is_empty() => self.low = true,
+            (true, true) if self.goto(self.cursor + 1) => {}
+            (false, true) => self.low = false,
+            (false, false) if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.low = true;
+            }
+            _ => {}
+        }
+    }
+    ///Moves the cursor with the given key on rows of the given amount of bytes. Returns if the
+    ///key moves the cursor
+    pub fn keydown(&mut self, key: KeyCode, row_bytes: usize) -> bool {
+        let row_start = self.cursor - self.cursor % row_bytes;
+        match key {
+            KeyCode::Left => self.move_nibble(false),
+            KeyCode::Right => self.move_nibble(true),
+            KeyCode::Up if self.cursor >= row_bytes => {
+                self.cursor -= row_bytes;
+            }
+            KeyCode::Down if self.cursor + row_bytes < self.bytes.len() => {
+                self.cursor += row_bytes;
+            }
+            KeyCode::Home => {
+                self.goto(row_start);
+            }
+            KeyCode::End => {
+                let end = (row_start + row_bytes).min(self.bytes.len());
+                self.goto(end.saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Down => {}
+            _ => return false,
+        }
+        true
+    }
+    ///Sets the bytes being searched and jumps to their first match after the position where the
+    ///search started. An empty query removes the search
+    pub fn search(&mut self, query: &str) {
+        let origin = *self.search_origin.get_or_insert(self.cursor);
+        self.goto(origin);
+        let pattern = parse_pattern(query);
+        self.pattern = (!pattern.is_empty()).then_some(pattern);
+        if let Some(start) = self.find_match(origin, true, true) {
+            self.goto(start);
+        }
+    }
+    ///Removes the current search. If it was still being typed, the cursor goes back to where it
+    ///started
+    pub fn clear_search(&mut self) {
+        if let Some(origin) = self.search_origin.take() {
+            self.goto(origin);
+        }
+        self.pattern = None;
+    }
+    ///Stops following the query being typed, keeping the cursor where it is
+    #[inline]
+    pub fn confirm_search(&mut self) {
+        self.search_origin = None;
+    }
+    ///Moves the cursor to the next match of the current search, backwards if not forward. Wraps
+    ///around the bytes. Returns if a match was found
+    pub fn search_next(&mut self, forward: bool) -> bool {
+        self.confirm_search();
+        match self.find_match(self.cursor, forward, false) {
+            Some(start) => self.goto(start),
+            None => false,
+        }
+    }
+    ///Finds where the first match starting from the given offset is, wrapping around the bytes.
+    ///If inclusive, a match starting exactly at the offset is accepted
+    fn find_match(&self, from: usize, forward: bool, inclusive: bool) -> Option<usize> {
+        let pattern = self.pattern.as_ref()?;
+        let starts: Vec<usize> = self
+            .bytes
+            .windows(pattern.len())
+            .enumerate()
+            .filter(|(_, window)| window == pattern)
+            .map(|(start, _)| start)
+            .collect();
+        let found = if forward {
+            starts
+                .iter()
+                .find(|s| **s > from || (inclusive && **s == from))
+        } else {
+            starts.iter().rfind(|s| **s < from)
+        };
+        found
+            .or(if forward {
+                starts.first()
+            } else {
+                starts.last()
+            })
+            .copied()
+    }
+    ///Gets the byte ranges matching the current search that overlap the given range
+    fn matches_in(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        let Some(pattern) = &self.pattern else {
+            return Vec::new();
+        };
+        let first = range.start.saturating_sub(pattern.len() - 1);
+        let last = (range.end + pattern.len() - 1).min(self.bytes.len());
+        self.bytes[first..last]
+            .windows(pattern.len())
+            .enumerate()
+            .filter(|(_, window)| window == pattern)
+            .map(|(start, _)| first + start..first + start + pattern.len())
+            .collect()
+    }
+}
+
+///Reads a query for bytes as hex digits after a 0x prefix, spaces between bytes being optional,
+///or as the bytes of the text otherwise. A prefixed query with an odd amount of digits or
+///something that is not hex is searched as text too
+fn parse_pattern(query: &str) -> Vec<u8> {
+    let Some(hex) = query.strip_prefix("0x") else {
+        return query.as_bytes().to_vec();
+    };
+    let digits: Vec<u8> = hex
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()
+        .unwrap_or_default();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return query.as_bytes().to_vec();
+    }
+    digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect()
+}
+
+impl TextArea {
+    ///Checks if the buffer is shown as hex
+    #[inline]
+    pub fn is_hex(&self) -> bool {
+        self.hex.is_some()
+    }
+    #[inline]
+    pub fn hex(&self) -> Option<&HexView> {
+        self.hex.as_ref()
+    }
+    ///Shows the given bytes as hex in place of the content of the buffer
+    pub(super) fn open_hex(&mut self, bytes: Vec<u8>) {
+        self.clear_content();
+        self.hex = Some(HexView::new(bytes));
+        self.top.set(0);
+    }
+    ///Switches between showing the buffer as text and as hex, going through the bytes it would be
+    ///saved as. The history of the text is kept, the bytes edited as hex coming back as a single
+    ///undo unit
+    pub fn toggle_hex(&mut self) -> Result<(), EncodingError> {
+        match self.hex.take() {
+            Some(hex) => {
+                let fallback = encoding_for_label(&self.settings.fallback_encoding)?;
+                let (format, content) = match FileFormat::decode(&hex.bytes, fallback) {
+                    Ok(decoded) => decoded,
+                    Err(e) => {
+                        self.hex = Some(hex);
+                        return Err(e);
+                    }
+                };
+                let Some(text) = hex.text else {
+                    self.load(format, &content);
+                    return Ok(());
+                };
+                self.load(format, &text.content);
+                self.history = text.history;
+                if content != text.content {
+                    let end = self.document.byte_to_pos(self.document.len_bytes());
+                    self.history.begin_group();
+                    self.remove_between((0, 0), end);
+                    self.insert_at((0, 0), &content);
+                    self.history.end_group();
+                }
+                self.set_cursor(text.cursor);
+            }
+            None => {
+                let content = self.to_string();
+                let bytes = self.format.encode(&content)?;
+                let text = HiddenText {
+                    content,
+                    history: std::mem::take(&mut self.history),
+                    cursor: (self.y, self.x),
+                };
+                self.open_hex(bytes);
+                if let Some(hex) = &mut self.hex {
+                    hex.text = Some(text);
+                }
+            }
+        }
+        Ok(())
+    }
+    ///Moves the cursor to the given byte offset of the buffer. Returns false if it is past the end
+    pub fn goto_offset(&mut self, offset: usize) -> bool {
+        if let Some(hex) = &mut self.hex {
+            return hex.goto(offset);
+        }
+        if offset > self.document.len_bytes() {
+            return false;
+        }
+        let offset = self
+            .document
+            .char_to_byte(self.document.byte_to_char(offset));
+        self.set_cursor(self.document.byte_to_pos(offset));
+        true
+    }
+    ///Gets how many bytes are shown on each row of the hex view, as many as fit up to 16
+    fn row_bytes(&self) -> usize {
+        //each byte takes 3 columns as hex and 1 as text, with a column between both
+        ((self.w() as usize).saturating_sub(OFFSET_COLS + 1) / 4).clamp(1, MAX_ROW_BYTES)
+    }
+    ///Handles the given key on the hex view. Returns false if the buffer is not shown as hex or
+    ///the key is not used by it
+    pub fn hex_keydown(&mut self, key: KeyCode) -> bool {
+        let row_bytes = self.row_bytes();
+        self.hex
+            .as_mut()
+            .is_some_and(|hex| hex.keydown(key, row_bytes))
+    }
+    ///Gets the row shown at the top of the hex view, moving it to have the cursor inside it
+    fn hex_top_row(&self, hex: &HexView) -> usize {
+        let row = hex.cursor / self.row_bytes();
+        let top = self
+            .top
+            .get()
+            .min(row)
+            .max((row + 1).saturating_sub(self.text_rows()));
+        self.top.set(top);
+        top
+    }
+    ///Gets the position of the cursor inside the hex view, on the nibble it is at
+    pub(super) fn hex_cursor(&self, hex: &HexView) -> (usize, usize) {
+        let row_bytes = self.row_bytes();
+        let x = OFFSET_COLS + 3 * (hex.cursor % row_bytes) + hex.low as usize;
+        (x, hex.cursor / row_bytes - self.hex_top_row(hex))
+    }
+    ///Renders the rows of the hex view, as the offset of their first byte, the bytes as hex and
+    ///the bytes as text, showing the ones that are not printable ASCII as '.'
+    pub(super) fn render_hex(
+        &self,
+        hex: &HexView,
+        base: Style,
+        select_style: Style,
+        search_style: Style,
+        search_current_style: Style,
+        buf: &mut Buffer,
+    ) {
+        let row_bytes = self.row_bytes();
+        let top = self.hex_top_row(hex);
+        let len = hex.bytes.len();
+        let shown = top * row_bytes..((top + self.text_rows()) * row_bytes).min(len);
+        let mut styles = vec![base; shown.len()];
+        for range in hex.matches_in(shown.clone()) {
+            let style = if range.start == hex.cursor {
+                search_current_style
+            } else {
+                search_style
+            };
+            for byte in range.start.max(shown.start)..range.end.min(shown.end) {
+                styles[byte - shown.start] = style;
+            }
+        }
+        let mut lines = Vec::new();
+        let mut offset = shown.start;
+        //an empty file still shows its first row
+        while offset < shown.end || (offset == 0 && lines.is_empty()) {
+            let row = offset..(offset + row_bytes).min(len);
+            let mut spans = vec![Span::styled(format!("{offset:08x}  "), base)];
+            for byte in row.clone() {
+                let style = styles[byte - shown.start];
+                spans.push(Span::styled(format!("{:02x}", hex.bytes[byte]), style));
+                spans.push(Span::styled(" ", base));
+            }
+            spans.push(Span::styled(
+                " ".repeat(3 * (row_bytes - row.len()) + 1),
+                base,
+            ));
+            for byte in row.clone() {
+                let b = hex.bytes[byte];
+                let c = if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                };
+                let style = if byte == hex.cursor {
+                    select_style
+                } else {
+                    styles[byte - shown.start]
+                };
+                spans.push(Span::styled(c.to_string(), style));
+            }
+            lines.push(Line::from(spans));
+            offset = row.end.max(offset + 1);
+        }
+        Paragraph::new(lines).render(self.area(), buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_pattern;
+
+    #[test]
+    fn patterns_are_text_without_a_prefix() {
+        assert_eq!(parse_pattern("ab"), b"ab");
+        assert_eq!(parse_pattern("00ff"), b"00ff");
+    }
+
+    #[test]
+    fn prefixed_patterns_are_bytes() {
+        assert_eq!(parse_pattern("0xab"), [0xab]);
+        assert_eq!(parse_pattern("0x00 ff 1"), b"0x00 ff 1");
+        assert_eq!(parse_pattern("0x00 ff 10"), [0x00, 0xff, 0x10]);
+        assert_eq!(parse_pattern("0xzz"), b"0xzz");
+    }
+}
@@ -0,0 +1,187 @@
+use crate::helpers::{history::BufferPos, min_max};
+
+use super::{brackets::BRACKETS, TextArea};
+
+impl TextArea {
+    ///Gets the text a level of indentation is written with
+    pub(super) fn indent_unit(&self) -> String {
+        if self.expandtab {
+            " ".repeat(self.indent_width)
+        } else {
+            "\t".to_string()
+        }
+    }
+    ///Gets the whitespace the line at the given index starts with
+    fn leading_whitespace(&self, idx: usize) -> String {
+        self.document
+            .line_str(idx)
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect()
+    }
+    ///Removes a level from the end of the given indentation
+    fn outdented(&self, mut indent: String) -> String {
+        if indent.ends_with('\t') {
+            indent.pop();
+        } else {
+            let spaces = indent.len() - indent.trim_end_matches(' ').len();
+            indent.truncate(indent.len() - spaces.min(self.indent_width));
+        }
+        indent
+    }
+    ///Finds the line of the innermost bracket opened before the given byte that is still open
+    ///there. Only the brackets that are openers of the language count
+    fn unclosed_bracket(&self, byte: usize) -> Option<usize> {
+        let openers = self.language()?.openers;
+        let rope = self.document.rope();
+        let char_idx = rope.byte_to_char(byte);
+        let mut depth = 0;
+        for (idx, c) in (0..char_idx).rev().zip(rope.chars_at(char_idx).reversed()) {
+            let Some((open, _)) = BRACKETS.iter().find(|(o, cl)| *o == c || *cl == c) else {
+                continue;
+            };
+            if !openers.contains(*open) {
+                continue;
+            }
+            if *open != c {
+                depth += 1;
+            } else if depth == 0 {
+                return Some(rope.char_to_line(idx));
+            } else {
+                depth -= 1;
+            }
+        }
+        None
+    }
+    ///Gets the indentation of text starting at the given byte that goes after the given position,
+    ///the end of the line before it. Follows the syntax tree where it can be trusted. Otherwise
+    ///goes a level deeper than the line of the bracket it is inside of or, outside of them,
+    ///continues the indentation of the line before, a level deeper if it ends with an opener
+    pub(super) fn indentation_for(&mut self, before: Option<BufferPos>, byte: usize) -> String {
+        let document = &self.document;
+        let hint = self
+            .syntax
+            .as_mut()
+            .and_then(|s| s.get_mut().indent_hint(document, byte));
+        let trusted = hint.filter(|hint| !hint.broken);
+        let anchor = match trusted {
+            Some(hint) => hint.anchor,
+            None => self.unclosed_bracket(byte),
+        };
+        let indent = if let Some(row) = anchor {
+            self.leading_whitespace(row) + &self.indent_unit()
+        } else if let (None, Some((y, x))) = (trusted, before) {
+            let line = self.document.line_str(y);
+            let openers = self.language().map_or("", |l| l.openers);
+            let base = self.leading_whitespace(y);
+            match line[..x].trim_end().chars().last() {
+                Some(c) if openers.contains(c) => base + &self.indent_unit(),
+                _ => base,
+            }
+        } else {
+            String::new()
+        };
+        match hint {
+            Some(hint) if hint.outdent => self.outdented(indent),
+            _ => indent,
+        }
+    }
+    ///Replaces the whitespace the line at the given index starts with by the given indentation.
+    ///The cursor and the selection anchor stay on the same text
+    fn set_indentation(&mut self, idx: usize, indent: &str) {
+        let line = self.document.line_string(idx);
+        let leading = line.len() - line.trim_start().len();
+        if line[..leading] == *indent {
+            return;
+        }
+        self.remove_between((idx, 0), (idx, leading));
+        self.insert_at((idx, 0), indent);
+        let shift = |(y, x): BufferPos| match x.checked_sub(leading) {
+            _ if y != idx => (y, x),
+            Some(after) => (y, indent.len() + after),
+            None => (y, indent.len()),
+        };
+        self.selection_anchor = shift(self.selection_anchor);
+        (self.y, self.x) = shift((self.y, self.x));
+    }
+    ///Gets the first and the last line of the selection or of the block, the line of the cursor
+    ///when nothing is selected
+    fn selected_lines(&self) -> (usize, usize) {
+        match self.selection_range() {
+            Some((start, end)) => (start.0, end.0),
+            None if self.is_block_selecting() => min_max(self.selection_anchor.0, self.y),
+            None => (self.y, self.y),
+        }
+    }
+    ///Indents again the selected lines, or the current one when nothing is selected, as new lines
+    ///get indented. Blank lines lose their whitespace
+    pub fn reindent(&mut self) {
+        let (first, last) = self.selected_lines();
+        self.history.begin_group();
+        for idx in first..=last {
+            let line = self.document.line_string(idx);
+            let leading = line.len() - line.trim_start().len();
+            let indent = if leading == line.len() {
+                String::new()
+            } else {
+                let before = (0..idx)
+                    .rev()
+                    .find(|prev| !self.document.line_string(*prev).trim().is_empty())
+                    .map(|prev| (prev, self.document.line_len(prev)));
+                let byte = self.document.line_to_byte(idx) + leading;
+                self.indentation_for(before, byte)
+            };
+            self.set_indentation(idx, &indent);
+        }
+        self.history.end_group();
+    }
+    ///Adds a level of indentation to the selected lines, skipping the blank ones, or removes one
+    ///from them if not deeper
+    fn shift_lines(&mut self, deeper: bool) {
+        let (first, last) = self.selected_lines();
+        self.history.begin_group();
+        for idx in first..=last {
+            if deeper && self.document.line_string(idx).trim().is_empty() {
+                continue;
+            }
+            let leading = self.leading_whitespace(idx);
+            let indent = if deeper {
+                leading + &self.indent_unit()
+            } else {
+                self.outdented(leading)
+            };
+            self.set_indentation(idx, &indent);
+        }
+        self.history.end_group();
+    }
+    ///Handles the Tab key. Indents the selected lines, or the current one when the cursor is inside
+    ///its indentation. Otherwise writes a tab at the cursor, or the spaces up to the next level of
+    ///indentation with expandtab
+    pub fn tab(&mut self) {
+        if self.large.is_some() || self.hex.is_some() {
+            return;
+        }
+        let line = self.document.line_string(self.y);
+        let leading = line.len() - line.trim_start().len();
+        let selecting = self.is_selecting() || self.is_block_selecting();
+        if selecting || (self.x <= leading && leading < line.len()) {
+            self.shift_lines(true);
+            return;
+        }
+        let tab = if self.expandtab {
+            let col = self.document.display_col((self.y, self.x), self.tab_width);
+            " ".repeat(self.indent_width - col % self.indent_width.max(1))
+        } else {
+            "\t".to_string()
+        };
+        let end = self.insert_at((self.y, self.x), &tab);
+        self.set_cursor(end);
+    }
+    ///Handles the Shift-Tab key, removing a level of indentation from the selected lines or the
+    ///current one
+    pub fn backtab(&mut self) {
+        if self.large.is_none() && self.hex.is_none() {
+            self.shift_lines(false);
+        }
+    }
+}
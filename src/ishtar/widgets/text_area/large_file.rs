@@ -0,0 +1,566 @@
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::KeyCode,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+use crate::helpers::{graphemes, search::Search};
+
+use super::{paint_line, TextArea};
+
+const INDEX_STRIDE: usize = 256; //lines between the offsets kept by the line index
+const INDEX_CHUNK: usize = 1 << 20; //bytes read at once while indexing
+const SEARCH_PREVIEW: usize = 1 << 20; //bytes looked at while the query is being typed
+
+///Offsets of every INDEX_STRIDE lines of a file, built in the background
+#[derive(Debug)]
+struct LineIndex {
+    checkpoints: Vec<u64>, //offsets of the lines 0, INDEX_STRIDE, 2 * INDEX_STRIDE...
+    lines: usize,          //lines found so far
+    indexed: u64,          //bytes looked at so far
+    done: bool,
+}
+
+///A file too big to be read at once. It is shown read-only, reading only the lines on the view
+#[derive(Debug)]
+pub struct LargeFile {
+    path: PathBuf,
+    size: u64,
+    index: Arc<Mutex<LineIndex>>,
+    cancelled: Arc<AtomicBool>, //stops the indexing when the file is closed
+    window: RefCell<(usize, Vec<String>)>, //the lines last read and the index of the first one
+    y: usize,
+    x: usize, //in bytes
+    search: Option<Search>,
+    search_origin: Option<(usize, usize)>, //where the cursor was when the search started
+}
+
+impl LargeFile {
+    ///Opens the file at the given path and starts indexing its lines
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let size = std::fs::metadata(&path)?.len();
+        let file = File::open(&path)?;
+        let index = Arc::new(Mutex::new(LineIndex {
+            checkpoints: vec![0],
+            lines: 1,
+            indexed: 0,
+            done: false,
+        }));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (thread_index, thread_cancelled) = (index.clone(), cancelled.clone());
+        thread::spawn(move || build_index(file, &thread_index, &thread_cancelled));
+        Ok(Self {
+            path,
+            size,
+            index,
+            cancelled,
+            window: RefCell::new((0, Vec::new())),
+            y: 0,
+            x: 0,
+            search: None,
+            search_origin: None,
+        })
+    }
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    #[inline]
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.y, self.x)
+    }
+    ///Gets how many lines have been found so far
+    pub fn lines(&self) -> usize {
+        self.index.lock().unwrap().lines
+    }
+    ///Gets the percentage of the file already indexed, none if it is done
+    pub fn progress(&self) -> Option<u64> {
+        let index = self.index.lock().unwrap();
+        (!index.done).then(|| index.indexed * 100 / self.size.max(1))
+    }
+    ///Gets a reader at the init of the closest line before the given one that is indexed, with
+    ///the index of that line
+    fn reader_at(&self, idx: usize) -> io::Result<(BufReader<File>, usize)> {
+        let (offset, line) = {
+            let index = self.index.lock().unwrap();
+            let checkpoint = (idx / INDEX_STRIDE).min(index.checkpoints.len() - 1);
+            (index.checkpoints[checkpoint], checkpoint * INDEX_STRIDE)
+        };
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        Ok((BufReader::new(file), line))
+    }
+    ///Reads the given amount of lines starting at the given one, keeping them to be read again.
+    ///Gets less lines if the file finishes before
+    pub fn read_lines(&self, first: usize, count: usize) -> Vec<String> {
+        if let Some(lines) = self.cached_lines(first, count) {
+            return lines;
+        }
+        let lines = self.read_uncached(first, count);
+        *self.window.borrow_mut() = (first, lines.clone());
+        lines
+    }
+    ///Gets the given lines if they were the last ones read
+    fn cached_lines(&self, first: usize, count: usize) -> Option<Vec<String>> {
+        let (cached, lines) = &*self.window.borrow();
+        (*cached <= first && first + count <= cached + lines.len())
+            .then(|| lines[first - cached..first - cached + count].to_vec())
+    }
+    fn read_uncached(&self, first: usize, count: usize) -> Vec<String> {
+        let Ok((mut reader, mut idx)) = self.reader_at(first) else {
+            return Vec::new();
+        };
+        let mut lines = Vec::with_capacity(count);
+        while lines.len() < count {
+            let Some(line) = read_line(&mut reader) else {
+                break;
+            };
+            if idx >= first {
+                lines.push(line);
+            }
+            idx += 1;
+        }
+        lines
+    }
+    ///Gets the line at the given index if the file has it
+    pub fn line(&self, idx: usize) -> Option<String> {
+        self.cached_lines(idx, 1)
+            .unwrap_or_else(|| self.read_uncached(idx, 1))
+            .pop()
+    }
+    ///Moves the cursor to the init of the given line. Returns false if the file has no such line
+    ///or it was not indexed yet, so the file is never read further than from the closest offset
+    ///on the index
+    pub fn goto_line(&mut self, idx: usize) -> bool {
+        if idx >= self.lines() || self.line(idx).is_none() {
+            return false;
+        }
+        self.y = idx;
+        self.x = 0;
+        true
+    }
+    ///Moves the cursor to the given line, keeping it on the same display column
+    fn move_to_line(&mut self, idx: usize, tab_width: usize) {
+        if let Some(line) = self.line(idx) {
+            let current = self.line(self.y).unwrap_or_default();
+            let col = graphemes::display_col(&current, self.x, tab_width);
+            self.y = idx;
+            self.x = graphemes::byte_at_col(&line, col, tab_width);
+        }
+    }
+    ///Moves the cursor with the given key, going a page for each amount of rows. Keys that would
+    ///edit the file are ignored. Returns if the key was used
+    pub fn keydown(&mut self, key: KeyCode, rows: usize, tab_width: usize) -> bool {
+        let line = self.line(self.y).unwrap_or_default();
+        match key {
+            KeyCode::Up if self.y > 0 => self.move_to_line(self.y - 1, tab_width),
+            KeyCode::Down => self.move_to_line(self.y + 1, tab_width),
+            KeyCode::PageUp => self.move_to_line(self.y.saturating_sub(rows), tab_width),
+            KeyCode::PageDown => {
+                let last = self.y + rows;
+                match self.line(last) {
+                    Some(_) => self.move_to_line(last, tab_width),
+                    None => self.move_to_line(self.lines() - 1, tab_width),
+                }
+            }
+            KeyCode::Left if self.x > 0 => self.x = graphemes::prev_grapheme(&line, self.x),
+            KeyCode::Left if self.y > 0 => {
+                self.y -= 1;
+                self.x = self.line(self.y).map_or(0, |l| l.len());
+            }
+            KeyCode::Right if self.x < line.len() => {
+                self.x = graphemes::next_grapheme(&line, self.x)
+            }
+            KeyCode::Right => {
+                self.goto_line(self.y + 1);
+            }
+            KeyCode::Home => self.x = 0,
+            KeyCode::End => self.x = line.len(),
+            KeyCode::Up
+            | KeyCode::Left
+            | KeyCode::Char(_)
+            | KeyCode::Backspace
+            | KeyCode::Delete
+            | KeyCode::Enter
+            | KeyCode::Tab
+            | KeyCode::BackTab => {}
+            _ => return false,
+        }
+        true
+    }
+    ///Sets the query being searched and jumps to its first match close after the position where
+    ///the search started. An empty query removes the search
+    pub fn search(&mut self, query: &str) -> Result<(), regex::Error> {
+        let origin = *self.search_origin.get_or_insert((self.y, self.x));
+        (self.y, self.x) = origin;
+        if query.is_empty() {
+            self.search = None;
+            return Ok(());
+        }
+        let search = Search::new(query);
+        self.search = search.as_ref().ok().cloned();
+        if let Some(found) = self.find_match(origin, true, true, Some(SEARCH_PREVIEW)) {
+            (self.y, self.x) = found;
+        }
+        search.map(|_| ())
+    }
+    ///Removes the current search. If it was still being typed, the cursor goes back to where it
+    ///started
+    pub fn clear_search(&mut self) {
+        if let Some(origin) = self.search_origin.take() {
+            (self.y, self.x) = origin;
+        }
+        self.search = None;
+    }
+    #[inline]
+    pub fn confirm_search(&mut self) {
+        self.search_origin = None;
+    }
+    ///Moves the cursor to the next match of the current search, backwards if not forward,
+    ///reading through the whole file. Returns if a match was found
+    pub fn search_next(&mut self, forward: bool) -> bool {
+        self.confirm_search();
+        match self.find_match((self.y, self.x), forward, false, None) {
+            Some(found) => {
+                (self.y, self.x) = found;
+                true
+            }
+            None => false,
+        }
+    }
+    ///Finds the first match from the given position, reading the file line by line and wrapping
+    ///around it. If there is a limit, gives up after reading that amount of bytes without wrapping
+    fn find_match(
+        &self,
+        from: (usize, usize),
+        forward: bool,
+        inclusive: bool,
+        limit: Option<usize>,
+    ) -> Option<(usize, usize)> {
+        let search = self.search.as_ref()?;
+        if !forward {
+            return self.find_match_backwards(search, from);
+        }
+        let (mut reader, mut idx) = self.reader_at(from.0).ok()?;
+        let mut read = 0;
+        while let Some(line) = read_line(&mut reader) {
+            read += line.len() + 1;
+            let found = search
+                .find_all(&line)
+                .into_iter()
+                .map(|m| (idx, m.start))
+                .find(|pos| *pos > from || (inclusive && *pos == from));
+            if found.is_some() {
+                return found;
+            }
+            if limit.is_some_and(|limit| read > limit) {
+                return None;
+            }
+            idx += 1;
+        }
+        //wraps around from the init of the file
+        let (mut reader, mut idx) = self.reader_at(0).ok()?;
+        while idx <= from.0 && limit.is_none() {
+            let line = read_line(&mut reader)?;
+            if let Some(m) = search.find_all(&line).first() {
+                return Some((idx, m.start));
+            }
+            idx += 1;
+        }
+        None
+    }
+    ///Finds the last match before the given position, wrapping around the file. Lines can only be
+    ///read forwards, so they are read by blocks of INDEX_STRIDE lines starting from the block of
+    ///the position, stopping at the first block with a match. While the file is being indexed,
+    ///only the lines found so far are wrapped into
+    fn find_match_backwards(
+        &self,
+        search: &Search,
+        from: (usize, usize),
+    ) -> Option<(usize, usize)> {
+        let blocks = self.index.lock().unwrap().checkpoints.len();
+        let first = (from.0 / INDEX_STRIDE).min(blocks - 1);
+        let last_in_block = |block: usize, before: Option<(usize, usize)>| {
+            let start = block * INDEX_STRIDE;
+            let lines = self.read_uncached(start, INDEX_STRIDE);
+            lines
+                .iter()
+                .enumerate()
+                .flat_map(|(i, line)| {
+                    search
+                        .find_all(line)
+                        .into_iter()
+                        .map(move |m| (start + i, m.start))
+                })
+                .rfind(|pos| before.is_none_or(|before| *pos < before))
+        };
+        (0..=first)
+            .rev()
+            .find_map(|block| last_in_block(block, Some(from)))
+            .or_else(|| {
+                (first..blocks)
+                    .rev()
+                    .find_map(|block| last_in_block(block, None))
+            })
+    }
+}
+
+impl Drop for LargeFile {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+///Reads the next line of the reader without its line ending, replacing bytes that are not UTF-8
+fn read_line(reader: &mut BufReader<File>) -> Option<String> {
+    let mut buf = Vec::new();
+    if reader.read_until(b'\n', &mut buf).ok()? == 0 {
+        return None;
+    }
+    if buf.last() == Some(&b'\n') {
+        buf.pop();
+        if buf.last() == Some(&b'\r') {
+            buf.pop();
+        }
+    }
+    Some(String::from_utf8_lossy(&buf).into_owned())
+}
+
+///Finds the lines of the file, keeping the offset of every INDEX_STRIDE of them on the index
+fn build_index(mut file: File, index: &Mutex<LineIndex>, cancelled: &AtomicBool) {
+    let mut chunk = vec![0; INDEX_CHUNK];
+    let mut offset = 0;
+    let mut lines = 1;
+    while !cancelled.load(Ordering::Relaxed) {
+        let read = match file.read(&mut chunk) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        let mut checkpoints = Vec::new();
+        for (i, _) in chunk[..read]
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b == b'\n')
+        {
+            if lines % INDEX_STRIDE == 0 {
+                checkpoints.push(offset + i as u64 + 1);
+            }
+            lines += 1;
+        }
+        offset += read as u64;
+        let mut index = index.lock().unwrap();
+        index.checkpoints.extend(checkpoints);
+        index.lines = lines;
+        index.indexed = offset;
+    }
+    index.lock().unwrap().done = true;
+}
+
+impl TextArea {
+    ///Checks if the buffer is a large file shown without being loaded
+    #[inline]
+    pub fn is_large(&self) -> bool {
+        self.large.is_some()
+    }
+    #[inline]
+    pub fn large(&self) -> Option<&LargeFile> {
+        self.large.as_ref()
+    }
+    ///Checks if a file of the given size is opened as a large file
+    pub(super) fn is_large_size(&self, size: u64) -> bool {
+        let threshold = self.settings.large_file_threshold as u64 * 1024 * 1024;
+        threshold > 0 && size > threshold
+    }
+    ///Shows the file at the given path read-only, reading only the lines on the view
+    pub(super) fn open_large(&mut self, path: PathBuf) -> io::Result<()> {
+        let large = LargeFile::open(path)?;
+        self.clear_content();
+        self.large = Some(large);
+        self.top.set(0);
+        self.left.set(0);
+        Ok(())
+    }
+    ///Reads the whole large file into the buffer so it can be edited, keeping the cursor on the
+    ///same line
+    pub fn load_whole_file(&mut self) -> io::Result<()> {
+        let Some(large) = self.large.take() else {
+            return Ok(());
+        };
+        let (y, x) = large.cursor();
+        if let Err(e) = std::fs::read(large.path()).and_then(|bytes| self.load_bytes(bytes)) {
+            self.large = Some(large);
+            return Err(e);
+        }
+        self.set_cursor((y, x));
+        Ok(())
+    }
+    ///Handles the given key on the large file. Returns false if the buffer is not a large file or
+    ///the key is not used by it
+    pub fn large_keydown(&mut self, key: KeyCode) -> bool {
+        let (rows, tab_width) = (self.text_rows(), self.tab_width);
+        self.large
+            .as_mut()
+            .is_some_and(|large| large.keydown(key, rows, tab_width))
+    }
+    ///Moves the cursor to the init of the given line. Returns false if there is no such line, or
+    ///on a large file if it was not indexed yet
+    pub fn goto_line(&mut self, idx: usize) -> bool {
+        if let Some(large) = &mut self.large {
+            return large.goto_line(idx);
+        }
+        if idx >= self.document.len_lines() {
+            return false;
+        }
+        self.set_cursor((idx, 0));
+        true
+    }
+    ///Gets the first row and column of the large file shown, moving them to have the cursor
+    ///inside the view
+    fn large_view(&self, large: &LargeFile) -> (usize, usize) {
+        let (y, x) = large.cursor();
+        let top = self
+            .top
+            .get()
+            .min(y)
+            .max((y + 1).saturating_sub(self.text_rows()));
+        let col = graphemes::display_col(&large.line(y).unwrap_or_default(), x, self.tab_width);
+        let left = self
+            .left
+            .get()
+            .min(col)
+            .max((col + 1).saturating_sub(self.text_cols()));
+        self.top.set(top);
+        self.left.set(left);
+        (top, left)
+    }
+    ///Gets the position of the cursor inside the view of the large file
+    pub(super) fn large_cursor(&self, large: &LargeFile) -> (usize, usize) {
+        let (top, left) = self.large_view(large);
+        let (y, x) = large.cursor();
+        let col = graphemes::display_col(&large.line(y).unwrap_or_default(), x, self.tab_width);
+        (col - left, y - top)
+    }
+    ///Renders the lines of the large file shown on the view
+    pub(super) fn render_large(
+        &self,
+        large: &LargeFile,
+        base: Style,
+        search_style: Style,
+        search_current_style: Style,
+        buf: &mut Buffer,
+    ) {
+        let (top, left) = self.large_view(large);
+        let gutter = self.xoffset() - 1;
+        let cursor = large.cursor();
+        let lines: Vec<Line> = large
+            .read_lines(top, self.text_rows())
+            .into_iter()
+            .enumerate()
+            .map(|(i, content)| {
+                let idx = top + i;
+                let overlays: Vec<_> = large
+                    .search
+                    .iter()
+                    .flat_map(|search| search.find_all(&content))
+                    .map(|range| {
+                        let current = (idx, range.start) == cursor;
+                        let style = if current {
+                            search_current_style
+                        } else {
+                            search_style
+                        };
+                        (range, style)
+                    })
+                    .collect();
+                let mut spans = vec![Span::styled(format!("{idx:>gutter$} "), base)];
+                let mut start = graphemes::byte_at_col(&content, left, self.tab_width);
+                if graphemes::display_col(&content, start, self.tab_width) < left {
+                    start = graphemes::next_grapheme(&content, start);
+                    let blank = graphemes::display_col(&content, start, self.tab_width) - left;
+                    spans.push(Span::styled(" ".repeat(blank), base));
+                }
+                spans.extend(paint_line(
+                    &content,
+                    base,
+                    &overlays,
+                    start..content.len(),
+                    self.tab_width,
+                ));
+                Line::from(spans)
+            })
+            .collect();
+        Paragraph::new(lines).render(self.area(), buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, thread, time::Duration};
+
+    use crate::helpers::search::Search;
+
+    use super::{LargeFile, INDEX_STRIDE};
+
+    const LINES: usize = 10 * INDEX_STRIDE;
+
+    ///Writes a file where every hundredth line has a match of "needle" and opens it once indexed
+    fn indexed(name: &str) -> (PathBuf, LargeFile) {
+        let path = std::env::temp_dir().join(format!("ishtar-{name}-{}.txt", std::process::id()));
+        let content: String = (0..LINES)
+            .map(|i| match i % 100 {
+                0 => format!("{i} needle\n"),
+                _ => format!("{i} hay\n"),
+            })
+            .collect();
+        std::fs::write(&path, content).unwrap();
+        let large = LargeFile::open(path.clone()).unwrap();
+        while large.progress().is_some() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        (path, large)
+    }
+
+    #[test]
+    fn goto_line_stays_inside_the_file() {
+        let (path, mut large) = indexed("large-goto");
+        assert_eq!(large.lines(), LINES + 1);
+        assert!(large.goto_line(LINES - 1));
+        assert_eq!(large.cursor(), (LINES - 1, 0));
+        assert_eq!(large.line(LINES - 1).unwrap(), format!("{} hay", LINES - 1));
+        assert!(!large.goto_line(LINES + 1));
+        assert_eq!(large.cursor(), (LINES - 1, 0));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn searching_backwards_crosses_blocks_and_wraps() {
+        let (path, mut large) = indexed("large-search");
+        large.search = Search::new("needle").ok();
+        //a match on the same block, on the block before and wrapping to the last one
+        large.goto_line(INDEX_STRIDE + 150);
+        assert!(large.search_next(false));
+        assert_eq!(large.cursor(), (400, 4));
+        assert!(large.search_next(false));
+        assert_eq!(large.cursor(), (300, 4));
+        assert!(large.search_next(false));
+        assert_eq!(large.cursor(), (200, 4));
+        large.goto_line(0);
+        assert!(large.search_next(false));
+        assert_eq!(large.cursor(), (2500, 5));
+        std::fs::remove_file(path).unwrap();
+    }
+}
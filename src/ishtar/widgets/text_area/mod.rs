@@ -0,0 +1,1515 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    ffi::OsStr,
+    io::ErrorKind,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use isht::{configuration::EditorSettings, CmdTask};
+use ratatui::{
+    buffer::Buffer,
+    prelude::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+use unicode_normalization::char::compose;
+use unicode_segmentation::UnicodeSegmentation;
+
+use ropey::RopeSlice;
+use tree_sitter::{InputEdit, Point};
+
+use crate::helpers::{
+    document::Document,
+    file_format::{encoding_for_label, EncodingError, FileFormat, LineEnding},
+    graphemes,
+    history::{BufferPos, Edit, EditHistory},
+    languages::{LanguageInfo, LanguageRegistry},
+    min_max,
+    search::Search,
+    syntax::{highlight_color, Syntax},
+    word_bounds, Vec2,
+};
+
+use super::clipboard::IshtarClipboard;
+
+mod block;
+mod brackets;
+mod cursors;
+mod expand;
+mod folds;
+mod hex;
+mod indent;
+mod large_file;
+mod replace;
+mod search;
+mod viewport;
+mod wrap;
+use folds::Fold;
+use hex::HexView;
+use large_file::LargeFile;
+use replace::PendingReplace;
+pub use replace::ReplaceAnswer;
+
+///A selection as (anchor, cursor)
+type Selection = (BufferPos, BufferPos);
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum TextAreaMode {
+    Writing,
+    Selecting,
+    BlockSelecting,
+}
+///Writing buffer
+#[derive(Debug)]
+pub struct TextArea {
+    document: Document,
+    position: Vec2,
+    size: Vec2,
+    selection_anchor: BufferPos, //where the selection started, the cursor is the other end
+    x: usize,                    //cursorx, in bytes
+    y: usize,                    //cursory
+    cursors: Vec<Selection>,     //cursors other than the main one, as (anchor, cursor)
+    punctuator: Option<char>,
+    editing_file: Option<PathBuf>,
+    mode: TextAreaMode,
+    history: EditHistory,
+    search: Option<Search>,
+    search_origin: Option<BufferPos>, //where the cursor was when the search started being typed
+    replacing: Option<PendingReplace>,
+    expansions: Vec<(Selection, Selection)>, //selections before and after each expansion
+    languages: Arc<LanguageRegistry>,
+    language: Option<usize>,
+    syntax: Option<RefCell<Syntax>>, //parsed while rendering, only if it was edited
+    folds: Vec<Fold>,                //closed folds, sorted
+    auto_closers: Vec<BufferPos>,    //closers written by auto pairs, typing them steps past
+    top: Cell<usize>,                //first row shown, follows the cursor while rendering
+    left: Cell<usize>,               //first char column shown
+    wrap: bool,                      //soft wraps the lines too long to be shown
+    wrap_column: usize,              //0 to wrap at the width of the area
+    indent_width: usize,             //columns of a level of indentation
+    expandtab: bool,                 //indents with spaces instead of tabs
+    tab_width: usize,                //columns between tab stops
+    auto_pairs: bool,                //writes the closer of the brackets and quotes typed
+    settings: Arc<EditorSettings>,
+    format: FileFormat,       //line endings, trailing newline and BOM of the file
+    hex: Option<HexView>,     //the bytes of the file when it is shown as hex
+    large: Option<LargeFile>, //the file when it is too big to be loaded
+}
+
+impl TextArea {
+    pub fn new(
+        x: u16,
+        y: u16,
+        w: u16,
+        h: u16,
+        languages: Arc<LanguageRegistry>,
+        settings: Arc<EditorSettings>,
+    ) -> Self {
+        Self {
+            punctuator: None,
+            position: Vec2::new(x, y),
+            size: Vec2::new(w, h),
+            selection_anchor: (0, 0),
+            x: 0,
+            y: 0,
+            cursors: Vec::new(),
+            document: Document::new(),
+            editing_file: None,
+            mode: TextAreaMode::Writing,
+            history: EditHistory::new(),
+            search: None,
+            search_origin: None,
+            replacing: None,
+            expansions: Vec::new(),
+            languages,
+            language: None,
+            syntax: None,
+            folds: Vec::new(),
+            auto_closers: Vec::new(),
+            format: FileFormat::default(),
+            hex: None,
+            large: None,
+            top: Cell::new(0),
+            left: Cell::new(0),
+            wrap: settings.wrap,
+            wrap_column: settings.wrap_column,
+            indent_width: settings.indent_width,
+            expandtab: settings.expandtab,
+            tab_width: settings.tab_width,
+            auto_pairs: settings.auto_pairs,
+            settings,
+        }
+    }
+    pub fn enter_selection(&mut self) {
+        self.history.seal();
+        self.confirm_search();
+        self.mode = TextAreaMode::Selecting;
+        self.selection_anchor = (self.y, self.x);
+        for (anchor, cursor) in &mut self.cursors {
+            *anchor = *cursor;
+        }
+    }
+    pub fn enter_writing(&mut self) {
+        self.history.seal();
+        self.confirm_search();
+        self.mode = TextAreaMode::Writing;
+    }
+    ///Gets the area bounds of this writer
+    pub fn area(&self) -> Rect {
+        Rect {
+            x: self.position.x(),
+            y: self.position.y(),
+            width: self.size.x(),
+            height: self.size.y(),
+        }
+    }
+    ///Gets the line where the cursor is
+    pub fn line(&self) -> RopeSlice<'_> {
+        self.document.line(self.y)
+    }
+    #[inline]
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+    pub fn file_position(&self) -> (u16, u16) {
+        (0, self.size.y())
+    }
+    ///Gets the X position of the cursor inside the shown part of the buffer
+    #[inline]
+    pub fn cursor_x(&self) -> usize {
+        if let Some(hex) = &self.hex {
+            return self.hex_cursor(hex).0;
+        }
+        if let Some(large) = &self.large {
+            return self.large_cursor(large).0;
+        }
+        self.cursor_row_col() - self.left_col()
+    }
+    ///Gets the Y position of the cursor inside the shown part of the buffer
+    #[inline]
+    pub fn cursor_y(&self) -> usize {
+        if let Some(hex) = &self.hex {
+            return self.hex_cursor(hex).1;
+        }
+        if let Some(large) = &self.large {
+            return self.large_cursor(large).1;
+        }
+        self.cursor_row() - self.top_row()
+    }
+    ///Gets the position x of the area(left corner)
+    #[inline]
+    pub fn posx(&self) -> u16 {
+        self.position.x()
+    }
+    ///Gets the y position of the area(top corner)
+    #[inline]
+    pub fn posy(&self) -> u16 {
+        self.position.y()
+    }
+    pub fn set_posx(&mut self, x: u16) -> u16 {
+        let old = self.posx();
+        *self.position.x_mut() = x;
+        old
+    }
+    pub fn set_posy(&mut self, y: u16) -> u16 {
+        let old = self.posy();
+        *self.position.y_mut() = y;
+        old
+    }
+    pub fn set_w(&mut self, w: u16) -> u16 {
+        let old = self.size.x();
+        *self.size.x_mut() = w;
+        old
+    }
+    pub fn set_h(&mut self, h: u16) -> u16 {
+        let old = self.size.y();
+        *self.size.y_mut() = h;
+        old
+    }
+    ///Gets the X position of the cursor without checking bounds
+    #[inline]
+    pub fn x(&self) -> usize {
+        self.x
+    }
+    ///Gets the Y position of the cursor without checking bounds
+    #[inline]
+    pub fn y(&self) -> usize {
+        self.y
+    }
+    pub fn w(&self) -> u16 {
+        self.size.x()
+    }
+    pub fn h(&self) -> u16 {
+        self.size.y()
+    }
+    pub fn set_cursor_x(&mut self, x: usize) {
+        self.x = x.min(self.line_end(self.y));
+    }
+    pub fn set_cursor_y(&mut self, y: usize) {
+        self.y = y.min(self.document.len_lines() - 1);
+    }
+    ///Gets X offset from the left due to line number. Wide enough for the number of the last line.
+    ///The hex view has its offsets inside the area
+    pub fn xoffset(&self) -> usize {
+        if self.hex.is_some() {
+            return 0;
+        }
+        let lines = self
+            .large
+            .as_ref()
+            .map_or(self.document.len_lines(), |l| l.lines());
+        1 + (lines - 1).to_string().len()
+    }
+    ///Gets the content of the line at the given index
+    pub fn content(&self, idx: usize) -> Option<RopeSlice<'_>> {
+        (idx < self.document.len_lines()).then(|| self.document.line(idx))
+    }
+    ///Clears the contents of the writer
+    pub fn clear_content(&mut self) {
+        self.document = Document::new();
+        self.hex = None;
+        self.large = None;
+        self.reset_syntax();
+        self.folds.clear();
+        self.auto_closers.clear();
+        self.cursors.clear();
+        self.history.clear();
+        self.set_cursor((0, 0));
+    }
+    ////Sets the content of the writer to be the content of a file, remembering its format, and
+    ///returns the amount of lines set
+    pub fn set_content(&mut self, content: String) -> usize {
+        let (format, content) = FileFormat::detect(&content);
+        self.load(format, &content)
+    }
+    ///Sets the content of the writer to be the given content, read from a file with the given
+    ///format, and returns the amount of lines set
+    fn load(&mut self, format: FileFormat, content: &str) -> usize {
+        self.format = format;
+        self.hex = None;
+        self.large = None;
+        self.document = Document::from_str(content);
+        self.reset_syntax();
+        self.folds.clear();
+        self.auto_closers.clear();
+        self.cursors.clear();
+        self.history.clear();
+        self.set_cursor((self.y, self.x));
+        self.document.len_lines()
+    }
+    #[inline]
+    pub fn format(&self) -> FileFormat {
+        self.format
+    }
+    ///Changes the line ending the buffer is saved with between LF and CRLF and returns the new one.
+    ///A buffer with mixed line endings has the CRs kept on its lines removed, as a single undo
+    ///unit, and is saved with LF
+    pub fn toggle_line_ending(&mut self) -> LineEnding {
+        if self.format.mixed_endings {
+            self.format.mixed_endings = false;
+            self.history.begin_group();
+            for y in 0..self.document.len_lines() {
+                let len = self.document.line_len(y);
+                if len > 0 && self.document.line(y).byte(len - 1) == b'\r' {
+                    self.remove_between((y, len - 1), (y, len));
+                }
+            }
+            self.history.end_group();
+            self.set_cursor((self.y, self.x));
+            return self.format.line_ending;
+        }
+        self.format.line_ending = self.format.line_ending.toggled();
+        self.format.line_ending
+    }
+    ///Writes the given text at the given position without recording it. Returns the position where
+    ///the written text finishes
+    fn insert_text(&mut self, at: BufferPos, text: &str) -> BufferPos {
+        let start = self.document.pos_to_byte(at);
+        let end = self.document.insert(at, text);
+        self.edit_syntax(start, start, start + text.len(), at, at, end);
+        self.shift_folds(at, at, end);
+        self.shift_auto_closers(at, at, end);
+        self.shift_cursors(at, at, end);
+        end
+    }
+    ///Removes the text between the given positions without recording it and returns the removed
+    ///text
+    fn remove_text(&mut self, from: BufferPos, to: BufferPos) -> String {
+        let start = self.document.pos_to_byte(from);
+        let removed = self.document.remove(from, to);
+        self.edit_syntax(start, start + removed.len(), start, from, to, from);
+        self.shift_folds(from, to, from);
+        self.shift_auto_closers(from, to, from);
+        self.shift_cursors(from, to, from);
+        removed
+    }
+    ///Tells the syntax tree about an edit that replaced the bytes between start and old_end by
+    ///the ones between start and new_end
+    fn edit_syntax(
+        &mut self,
+        start_byte: usize,
+        old_end_byte: usize,
+        new_end_byte: usize,
+        start: BufferPos,
+        old_end: BufferPos,
+        new_end: BufferPos,
+    ) {
+        let point = |(row, column): BufferPos| Point { row, column };
+        if let Some(syntax) = &mut self.syntax {
+            syntax.get_mut().edit(&InputEdit {
+                start_byte,
+                old_end_byte,
+                new_end_byte,
+                start_position: point(start),
+                old_end_position: point(old_end),
+                new_end_position: point(new_end),
+            });
+        }
+    }
+    #[inline]
+    fn reset_syntax(&mut self) {
+        if let Some(syntax) = &mut self.syntax {
+            syntax.get_mut().reset();
+        }
+    }
+    ///Finds the language of the buffer from the editing file or the shebang on its first line and
+    ///picks the highlighter of it
+    fn update_language(&mut self) {
+        let first_line = self.document.line_string(0);
+        self.language = self
+            .languages
+            .detect(self.editing_file.as_deref(), &first_line);
+        self.syntax = self.language.and_then(|language| {
+            let highlights = self.languages.highlight_query(language)?;
+            let indents = self.languages.indent_query(language);
+            let language = self.languages.info(language).language();
+            Syntax::new(&language, highlights, indents).map(RefCell::new)
+        });
+        self.apply_filetype_settings();
+    }
+    #[inline]
+    pub fn languages(&self) -> &LanguageRegistry {
+        &self.languages
+    }
+    ///Gets the language the buffer is written in, if known
+    pub fn language(&self) -> Option<&'static LanguageInfo> {
+        self.language.map(|language| self.languages.info(language))
+    }
+    ///Writes the given text at the given position, recording it into the history and returns
+    ///where the text finishes
+    fn insert_at(&mut self, at: BufferPos, text: &str) -> BufferPos {
+        if text.is_empty() {
+            return at;
+        }
+        self.history.record(
+            Edit::Insert {
+                at,
+                content: text.to_string(),
+            },
+            (self.y, self.x),
+        );
+        let end = self.insert_text(at, text);
+        if text.contains('\n') {
+            self.history.seal();
+        }
+        end
+    }
+    ///Removes the text between the given positions, recording it into the history and returns
+    ///the removed text
+    fn remove_between(&mut self, from: BufferPos, to: BufferPos) -> String {
+        if from == to {
+            return String::new();
+        }
+        let cursor = (self.y, self.x);
+        let content = self.remove_text(from, to);
+        self.history.record(
+            Edit::Remove {
+                at: from,
+                content: content.clone(),
+            },
+            cursor,
+        );
+        if content.contains('\n') {
+            self.history.seal();
+        }
+        content
+    }
+    ///Places the cursor at the given position, keeping it inside the document. Opens the folds
+    ///hiding that position
+    fn set_cursor(&mut self, (y, x): BufferPos) {
+        self.y = y.min(self.document.len_lines() - 1);
+        self.reveal(self.y);
+        self.x = x.min(self.line_end(self.y));
+    }
+    ///Gets the byte where the editable content of the given line finishes. On a buffer with mixed
+    ///line endings, that is before the CR its CRLF lines keep
+    pub(super) fn line_end(&self, y: usize) -> usize {
+        let len = self.document.line_len(y);
+        if self.format.mixed_endings && len > 0 && self.document.line(y).byte(len - 1) == b'\r' {
+            len - 1
+        } else {
+            len
+        }
+    }
+    ///Reverts the last group of edits made on this buffer
+    pub fn undo(&mut self) {
+        let Some(group) = self.history.undo() else {
+            return;
+        };
+        for edit in group.edits.iter().rev() {
+            match edit {
+                Edit::Insert { at, .. } => {
+                    self.remove_text(*at, edit.end());
+                }
+                Edit::Remove { at, content } => {
+                    self.insert_text(*at, content);
+                }
+            }
+        }
+        self.set_cursor(group.cursor);
+        self.merge_cursors();
+    }
+    ///Reapplies the last group of edits that was undone
+    pub fn redo(&mut self) {
+        let Some(group) = self.history.redo() else {
+            return;
+        };
+        let mut cursor = group.cursor;
+        for edit in group.edits.iter() {
+            cursor = match edit {
+                Edit::Insert { at, content } => self.insert_text(*at, content),
+                Edit::Remove { at, .. } => {
+                    self.remove_text(*at, edit.end());
+                    *at
+                }
+            };
+        }
+        self.set_cursor(cursor);
+        self.merge_cursors();
+    }
+    ///Writes the given char and moves the cursor
+    fn move_after_insert(&mut self, c: char) {
+        let end = self.insert_at((self.y, self.x), c.encode_utf8(&mut [0; 4]));
+        self.set_cursor(end);
+    }
+    ///Writes the given char at every cursor checking for punctuators; Does nothing on
+    ///Selectionmode or on large files. On the hex view, overwrites the nibble under the cursor if
+    ///the char is a hex digit
+    pub fn write_char(&mut self, c: char) {
+        if self.mode != TextAreaMode::Writing || self.large.is_some() {
+            return;
+        }
+        if let Some(hex) = &mut self.hex {
+            hex.write_nibble(c);
+            return;
+        }
+        let punctuator = self.punctuator;
+        self.at_every_cursor(|area| {
+            area.punctuator = punctuator;
+            area.write_at_cursor(c);
+        });
+    }
+    ///Writes the given char at the cursor checking for punctuators
+    fn write_at_cursor(&mut self, c: char) {
+        if c.is_ascii_punctuation() {
+            if let Some(c) = self.punctuator {
+                self.move_after_insert(c);
+                self.punctuator = None;
+            }
+            self.write_punctuation(c);
+            return;
+        }
+        if let Some(punc) = self.punctuator {
+            if let Some(c) = compose(c, punc) {
+                self.move_after_insert(c);
+                self.punctuator = None;
+                return;
+            };
+            self.move_after_insert(punc);
+            self.move_after_insert(c);
+        } else {
+            self.move_after_insert(c);
+        }
+    }
+    ///Removes the char before every cursor. Does nothing on selection mode
+    pub fn backspace(&mut self) {
+        if self.mode != TextAreaMode::Writing {
+            return;
+        }
+        self.at_every_cursor(Self::backspace_at_cursor);
+    }
+    ///Removes the char before the cursor
+    fn backspace_at_cursor(&mut self) {
+        if self.inside_empty_pair() {
+            //the closer goes with the opener
+            let closer = self.document.next_grapheme_len((self.y, self.x));
+            let from = (
+                self.y,
+                self.x - self.document.prev_grapheme_len((self.y, self.x)),
+            );
+            self.remove_between(from, (self.y, self.x + closer));
+            self.set_cursor(from);
+            return;
+        }
+        let from = if self.x > 0 {
+            (
+                self.y,
+                self.x - self.document.prev_grapheme_len((self.y, self.x)),
+            )
+        } else if self.y > 0 {
+            (self.y - 1, self.line_end(self.y - 1))
+        } else {
+            return;
+        };
+        self.remove_between(from, (self.y, self.x));
+        self.set_cursor(from);
+    }
+    ///Removes the char forward to every cursor. Does nothing on selection mode.(Simply executes
+    ///the Delete key usage)
+    pub fn del(&mut self) {
+        if self.mode != TextAreaMode::Writing {
+            return;
+        }
+        self.at_every_cursor(Self::del_at_cursor);
+    }
+    ///Removes the char forward to the cursor
+    fn del_at_cursor(&mut self) {
+        let last = self.document.len_lines() - 1;
+        let line_len = self.line_end(self.y);
+        if line_len == 0 && self.y == last && self.y > 0 {
+            //Removing the last empty line makes the cursor go to the end of the above one
+            let from = (self.y - 1, self.line_end(self.y - 1));
+            self.remove_between(from, (self.y, 0));
+            self.set_cursor(from);
+        } else if self.x == line_len && self.y < last {
+            self.remove_between((self.y, self.x), (self.y + 1, 0));
+        } else if self.x < line_len {
+            let len = self.document.next_grapheme_len((self.y, self.x));
+            self.remove_between((self.y, self.x), (self.y, self.x + len));
+        }
+    }
+    ///Breaks the line at every cursor and makes them go downwards, indenting the new lines as the
+    ///code around them needs. Does nothing on selection mode
+    pub fn newline(&mut self) {
+        if self.mode != TextAreaMode::Writing {
+            return;
+        }
+        self.at_every_cursor(Self::newline_at_cursor);
+    }
+    ///Breaks the line at the cursor and makes it go downwards, indenting the new line
+    fn newline_at_cursor(&mut self) {
+        let (y, x) = (self.y, self.x);
+        let mut line = self.document.line_string(y);
+        //a CRLF line of a mixed buffer is broken into CRLF lines
+        let line_end = self.line_end(y);
+        let ending = if line_end < line.len() { "\r\n" } else { "\n" };
+        line.truncate(line_end);
+        let leading = line.len() - line.trim_start().len();
+        self.history.begin_group();
+        let indent = if x <= leading {
+            //the rest of the line keeps its own indentation
+            line[..x].to_string()
+        } else {
+            let blanks = line[x..].len() - line[x..].trim_start().len();
+            self.remove_between((y, x), (y, x + blanks));
+            let byte = self.document.pos_to_byte((y, x));
+            self.indentation_for(Some((y, x)), byte)
+        };
+        let end = if self.inside_empty_brackets() {
+            //the closer of an empty pair goes down to its own line
+            let inner = format!("{ending}{indent}{}", self.indent_unit());
+            let end = self.insert_at((y, x), &inner);
+            self.insert_at(end, &format!("{ending}{indent}"));
+            end
+        } else {
+            self.insert_at((y, x), &format!("{ending}{indent}"))
+        };
+        self.history.end_group();
+        self.set_cursor(end);
+    }
+    pub fn goto_init_of_line(&mut self) {
+        self.x = 0;
+    }
+    pub fn goto_end_of_line(&mut self) {
+        self.x = self.line_end(self.y);
+    }
+    pub fn goto_init_of_file(&mut self) {
+        self.x = 0;
+        self.y = 0;
+    }
+    pub fn goto_end_of_file(&mut self) {
+        self.y = self.visible_line(self.document.len_lines() - 1);
+        self.x = self.line_end(self.y);
+    }
+    ///Gets the byte range of the word under the given position, if there is one
+    pub fn word_at(&self, (y, x): BufferPos) -> Option<Range<usize>> {
+        let line = self.content(y)?.to_string();
+        word_bounds(&line)
+            .into_iter()
+            .find(|word| word.contains(&x))
+    }
+    ///Moves the cursor to the end of the current word or of the next one if already at the end.
+    ///Goes to the next lines while no word is found
+    pub fn goto_end_of_word(&mut self) {
+        let mut y = self.y;
+        let mut after = Some(self.x);
+        loop {
+            let line = self.document.line_str(y);
+            let next = word_bounds(&line)
+                .into_iter()
+                .find(|word| after.is_none_or(|x| word.end > x));
+            if let Some(word) = next {
+                self.set_cursor((y, word.end));
+                return;
+            }
+            if y == self.document.len_lines() - 1 {
+                self.set_cursor((y, line.len()));
+                return;
+            }
+            y += 1;
+            after = None;
+        }
+    }
+    ///Moves the cursor to the init of the current word or of the previous one if already at the
+    ///init. Goes to the previous lines while no word is found
+    pub fn goto_init_of_word(&mut self) {
+        let mut y = self.y;
+        let mut before = Some(self.x);
+        loop {
+            let line = self.document.line_str(y);
+            let prev = word_bounds(&line)
+                .into_iter()
+                .rfind(|word| before.is_none_or(|x| word.start < x));
+            if let Some(word) = prev {
+                self.set_cursor((y, word.start));
+                return;
+            }
+            if y == 0 {
+                self.set_cursor((0, 0));
+                return;
+            }
+            y -= 1;
+            before = None;
+        }
+    }
+    pub fn move_down(&mut self) {
+        if self.wrap && self.move_rows(1) {
+            return;
+        }
+        let Some(next) = self.next_visible_line(self.y).filter(|_| !self.wrap) else {
+            let len = self.line_end(self.y);
+            self.x = len - self.document.prev_grapheme_len((self.y, len));
+            return;
+        };
+        let col = self.document.display_col((self.y, self.x), self.tab_width);
+        self.y = next;
+        self.set_cursor_x(self.document.byte_at_col(self.y, col, self.tab_width));
+    }
+    pub fn move_up(&mut self) {
+        if self.wrap && self.move_rows(-1) {
+            return;
+        }
+        if self.y == 0 {
+            self.x = 0;
+            return;
+        }
+        let col = self.document.display_col((self.y, self.x), self.tab_width);
+        self.y = self.visible_line(self.y - 1);
+        self.set_cursor_x(self.document.byte_at_col(self.y, col, self.tab_width));
+    }
+    pub fn move_left(&mut self) {
+        if self.x == 0 {
+            if self.y > 0 {
+                self.y = self.visible_line(self.y - 1);
+                self.x = self.line_end(self.y);
+            }
+            return;
+        }
+        self.x -= self.document.prev_grapheme_len((self.y, self.x));
+    }
+    pub fn move_right(&mut self) {
+        if self.x < self.line_end(self.y) {
+            self.x += self.document.next_grapheme_len((self.y, self.x));
+        }
+    }
+    pub fn move_x(&mut self, n: i16) {
+        let mut dp = n - self.x as i16; //delta post. I really dont think anyone will create a line
+                                        //with 32764 chars
+        if dp > 0 {
+            while dp > 0 {
+                dp -= 1;
+                self.move_right();
+            }
+        } else {
+            while dp < 0 {
+                dp += 1;
+                self.move_left();
+            }
+        }
+    }
+    pub fn move_y(&mut self, n: i16) {
+        let mut dp = n - self.x as i16; //delta post
+        if dp > 0 {
+            while dp > 0 {
+                dp -= 1;
+                self.move_up();
+            }
+        } else {
+            while dp < 0 {
+                dp += 1;
+                self.move_down();
+            }
+        }
+    }
+    ///Totally resets the writer.
+    pub fn reset(&mut self) {
+        self.editing_file = None;
+        self.language = None;
+        self.syntax = None;
+        self.format = FileFormat::default();
+        self.apply_filetype_settings();
+        self.clear_content();
+    }
+    ///Opens the given file and set the writer content to be the file content, detecting its
+    ///encoding. Binary files are shown as hex and files over the large file threshold are shown
+    ///without being loaded. If the file does not exists, empties the writer and still sets the
+    ///editing file to be the given path, so writing it creates a new file with its contents. If
+    ///the file can not be read, the writer is left as it was
+    pub fn open_file(&mut self, path: PathBuf) -> std::io::Result<()> {
+        let mut fpath = std::env::current_dir()?;
+        fpath.push(&path);
+        match std::fs::metadata(&fpath) {
+            Ok(meta) if meta.is_file() && self.is_large_size(meta.len()) => {
+                self.open_large(fpath)?
+            }
+            Ok(_) => self.load_bytes(std::fs::read(fpath)?)?,
+            //a new file starts empty instead of keeping the buffer opened before
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                self.clear_content();
+                self.format = FileFormat::default();
+            }
+            Err(e) => return Err(e),
+        }
+        self.editing_file = Some(path);
+        self.update_language();
+        Ok(())
+    }
+    ///Sets the writer content to be the given file content, decoded with the encoding detected
+    ///for it or shown as hex if it is binary
+    fn load_bytes(&mut self, bytes: Vec<u8>) -> std::io::Result<()> {
+        if FileFormat::is_binary(&bytes) {
+            self.open_hex(bytes);
+            return Ok(());
+        }
+        let fallback = encoding_for_label(&self.settings.fallback_encoding)
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+        let (format, content) = FileFormat::decode(&bytes, fallback)
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
+        self.load(format, &content);
+        Ok(())
+    }
+    ///Reads the current file again in the encoding with the given name, discarding the changes
+    ///not saved
+    pub fn reopen_with_encoding(&mut self, label: &str) -> std::io::Result<()> {
+        let encoding = encoding_for_label(label)
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+        let Some(ref file) = self.editing_file else {
+            return Err(ErrorKind::NotFound.into());
+        };
+        let bytes = std::fs::read(std::env::current_dir()?.join(file))?;
+        let (format, content) = FileFormat::decode_as(&bytes, encoding)
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
+        self.load(format, &content);
+        self.update_language();
+        Ok(())
+    }
+    ///Makes the buffer be saved in the encoding with the given name, returning its canonical name
+    pub fn set_encoding(&mut self, label: &str) -> Result<&'static str, EncodingError> {
+        let encoding = encoding_for_label(label)?;
+        self.format.set_encoding(encoding);
+        Ok(encoding.name())
+    }
+    pub fn modify_file_name(&mut self, new_name: &str) {
+        if let Some(ref file) = self.editing_file {
+            self.editing_file = Some(file.with_file_name(new_name));
+        } else {
+            self.editing_file = Some(new_name.into())
+        }
+        self.update_language();
+    }
+    pub fn modify_file_extension(&mut self, new_name: &str) {
+        if let Some(ref file) = self.editing_file {
+            self.editing_file = Some(file.with_extension(new_name));
+        };
+        self.update_language();
+    }
+    pub fn file_extension(&self) -> Option<&OsStr> {
+        if let Some(ref file) = self.editing_file {
+            file.extension()
+        } else {
+            None
+        }
+    }
+    ///Close the files and clears the writer content
+    pub fn close_file(&mut self) {
+        self.clear_content();
+        self.editing_file = None;
+        self.language = None;
+        self.syntax = None;
+        self.format = FileFormat::default();
+        self.apply_filetype_settings();
+    }
+    pub fn file_name(&self) -> Option<&OsStr> {
+        if let Some(ref file) = self.editing_file {
+            file.file_name()
+        } else {
+            None
+        }
+    }
+    ///Saves into the file located as prefix + editiong_file the content of this writer, in the
+    ///format and encoding the file was read with. If the file does not exist, create a file and
+    ///write it. Large files can not be edited, so they are only copied if saved somewhere else
+    pub fn save(&self, prefix: &Path) -> std::io::Result<()> {
+        if let Some(ref file) = self.editing_file {
+            let prefixed = prefix.join(file);
+            if let Some(large) = &self.large {
+                if prefixed != large.path() {
+                    std::fs::copy(large.path(), &prefixed)?;
+                }
+                return Ok(());
+            }
+            if let Some(hex) = &self.hex {
+                return std::fs::write(&prefixed, hex.bytes());
+            }
+            let bytes = self
+                .format
+                .encode(&self.to_string())
+                .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
+            std::fs::write(&prefixed, bytes)
+        } else {
+            Ok(())
+        }
+    }
+    ///Pastes the given content at every cursor, replacing the selections if there are. If the
+    ///content has multiple lines, append insert them as well. When there are as many lines as
+    ///cursors, each cursor gets one of them in order
+    pub fn paste(&mut self, content: &str) -> CmdTask {
+        if self.large.is_some() {
+            return CmdTask::Null;
+        }
+        if self.is_block_selecting() {
+            return self.paste_into_block(content);
+        }
+        let ranges = self.cursor_ranges();
+        let mut texts = vec![content; ranges.len()];
+        let lines: Vec<&str> = content.split('\n').collect();
+        if ranges.len() > 1 && lines.len() == ranges.len() {
+            let mut order: Vec<usize> = (0..ranges.len()).collect();
+            order.sort_by_key(|idx| ranges[*idx]);
+            for (idx, line) in order.into_iter().zip(lines) {
+                texts[idx] = line;
+            }
+        }
+        let mut texts = texts.into_iter();
+        self.history.begin_group();
+        self.at_every_cursor(|area| {
+            //Pasting over a selection replaces it
+            if let Some((start, end)) = area.selection_range() {
+                area.remove_between(start, end);
+                area.set_cursor(start);
+            }
+            let end = area.insert_at((area.y, area.x), texts.next().unwrap_or(content));
+            area.set_cursor(end);
+            area.selection_anchor = end;
+        });
+        self.history.end_group();
+        if self.is_selecting() {
+            self.enter_writing();
+        }
+        CmdTask::EnterModify
+    }
+    ///Removes the line of the cursor, or the closed fold on it. Returns the removed text
+    pub fn delete_line(&mut self) -> String {
+        self.history.seal();
+        let last = self.document.len_lines() - 1;
+        let x = self.x;
+        //A closed fold is deleted as a whole
+        let end = self.closed_fold_at(self.y).map_or(self.y, |(_, end)| end);
+        let removed = if self.y == 0 && end == last {
+            self.remove_between((0, 0), (last, self.document.line_len(last)))
+        } else if end < last {
+            self.remove_between((self.y, 0), (end + 1, 0))
+        } else {
+            let from = (self.y - 1, self.document.line_len(self.y - 1));
+            self.remove_between(from, (end, self.document.line_len(end)))
+        };
+        self.history.seal();
+        self.set_cursor((self.y, x));
+        removed
+    }
+    pub fn copy_line(&self, clipboard: &mut IshtarClipboard, is_virtual: bool) -> CmdTask {
+        if !self.is_selecting() {
+            return CmdTask::EnterModify;
+        }
+        if is_virtual {
+            clipboard.set_virtual(self.line().to_string());
+        } else {
+            clipboard.set(self.line().to_string());
+        }
+        CmdTask::EnterModify
+    }
+    pub fn copy_buffer(&self, clipboard: &mut IshtarClipboard, is_virtual: bool) -> CmdTask {
+        if !self.is_selecting() {
+            return CmdTask::EnterModify;
+        }
+        if is_virtual {
+            clipboard.set_virtual(self.to_string());
+        } else {
+            clipboard.set(self.to_string());
+        }
+        CmdTask::EnterModify
+    }
+    ///Gets the selected range ordered as (start, end). The end is not included in the selection.
+    ///A closed fold at the end of the selection is selected whole
+    pub fn selection_range(&self) -> Option<(BufferPos, BufferPos)> {
+        if !self.is_selecting() {
+            return None;
+        }
+        let (start, end) = min_max(self.selection_anchor, (self.y, self.x));
+        let end = match self.closed_fold_at(end.0) {
+            Some((_, last)) if start != end => (last, self.document.line_len(last)),
+            _ => end,
+        };
+        Some((start, end))
+    }
+    ///Gets the bytes of the line at the given index inside the given range, ordered as
+    ///(start, end), if the line is inside it
+    fn range_on_line(&self, (start, end): Selection, idx: usize) -> Option<Range<usize>> {
+        if idx < start.0 || idx > end.0 {
+            return None;
+        }
+        let from = if idx == start.0 { start.1 } else { 0 };
+        let to = if idx == end.0 {
+            end.1
+        } else {
+            self.document.line_len(idx)
+        };
+        Some(from..to)
+    }
+    ///Gets the text between the given positions
+    pub fn text_between(&self, from: BufferPos, to: BufferPos) -> String {
+        self.document.slice(from, to)
+    }
+    ///Gets the selected text. With several cursors, the text of each selection goes in its own
+    ///line, in the order they have in the buffer, as the rows of a block do
+    pub fn get_selection(&self) -> Option<String> {
+        let mut ranges = match self.block_ranges() {
+            Some(rows) => rows,
+            None => {
+                self.selection_range()?;
+                self.cursor_ranges()
+            }
+        };
+        ranges.sort();
+        let texts: Vec<String> = ranges
+            .into_iter()
+            .map(|(start, end)| self.text_between(start, end))
+            .collect();
+        Some(texts.join("\n"))
+    }
+    ///Removes the selected text of every cursor, leaving them where their selection started, and
+    ///goes back to writing mode. Each row of a block gets a cursor. Returns the removed text as
+    ///`get_selection` gives it
+    pub fn delete_selection(&mut self) -> Option<String> {
+        let removed = self.get_selection()?;
+        self.block_to_cursors();
+        self.history.seal();
+        self.at_every_cursor(|area| {
+            if let Some((start, end)) = area.selection_range() {
+                area.remove_between(start, end);
+                area.set_cursor(start);
+                area.selection_anchor = start;
+            }
+        });
+        self.history.seal();
+        self.enter_writing();
+        Some(removed)
+    }
+    pub fn is_selecting(&self) -> bool {
+        matches!(self.mode, TextAreaMode::Selecting)
+    }
+    pub fn render_colored(&self, colors: &Arc<HashMap<String, u32>>, buf: &mut Buffer) {
+        let fg = (**colors).get("text_fg").cloned().unwrap_or(0xffffff);
+        let base = Style::default().fg(Color::from_u32(fg));
+        let select_bg = (**colors).get("select_bg").cloned().unwrap_or(0xff0000);
+        let select_style = base.bg(Color::from_u32(select_bg));
+        let search_style = Style::default()
+            .fg(Color::from_u32(
+                (**colors).get("search_fg").cloned().unwrap_or(0x000000),
+            ))
+            .bg(Color::from_u32(
+                (**colors).get("search_bg").cloned().unwrap_or(0xffff00),
+            ));
+        let search_current_style = search_style.bg(Color::from_u32(
+            (**colors)
+                .get("search_current_bg")
+                .cloned()
+                .unwrap_or(0xff8800),
+        ));
+        let fold_style = Style::default().fg(Color::from_u32(
+            (**colors).get("fold_fg").cloned().unwrap_or(0x7f848e),
+        ));
+        let bracket_style = Style::default().bg(Color::from_u32(
+            (**colors)
+                .get("bracket_match_bg")
+                .cloned()
+                .unwrap_or(0x4b5263),
+        ));
+        let cursor_style = Style::default()
+            .fg(Color::from_u32(
+                (**colors).get("cursor_fg").cloned().unwrap_or(0x000000),
+            ))
+            .bg(Color::from_u32(
+                (**colors).get("cursor_bg").cloned().unwrap_or(0xbbbbbb),
+            ));
+        if let Some(large) = &self.large {
+            self.render_large(large, base, search_style, search_current_style, buf);
+            self.render_file_name(colors, buf);
+            return;
+        }
+        if let Some(hex) = &self.hex {
+            self.render_hex(
+                hex,
+                base,
+                select_style,
+                search_style,
+                search_current_style,
+                buf,
+            );
+            self.render_file_name(colors, buf);
+            return;
+        }
+        let visible = self.visible_rows();
+        let first = visible.first().map_or(0, |(idx, _)| *idx);
+        let last = visible.last().map_or(0, |(idx, _)| *idx);
+        let gutter = self.xoffset() - 1;
+        let left = self.left_col();
+        let highlights = match &self.syntax {
+            Some(syntax) => syntax
+                .borrow_mut()
+                .highlights(&self.document, first..last + 1),
+            None => Vec::new(),
+        };
+        let brackets = self.bracket_pair(
+            first.saturating_sub(brackets::SCAN_MARGIN)..last + brackets::SCAN_MARGIN,
+        );
+        let lines: Vec<Line> = visible
+            .into_iter()
+            .map(|(idx, shown)| {
+                let content = self.document.line_str(idx);
+                let mut overlays = Vec::new();
+                for (range, highlight) in highlights.get(idx - first).into_iter().flatten() {
+                    if let Some(color) = highlight_color(colors, *highlight) {
+                        overlays.push((range.clone(), Style::default().fg(Color::from_u32(color))));
+                    }
+                }
+                for (y, x) in brackets.into_iter().flat_map(|(a, b)| [a, b]) {
+                    if y == idx {
+                        overlays.push((x..x + 1, bracket_style));
+                    }
+                }
+                for range in self.matches_on_line(idx) {
+                    let current = idx == self.y && range.start == self.x;
+                    let style = if current {
+                        search_current_style
+                    } else {
+                        search_style
+                    };
+                    overlays.push((range, style));
+                }
+                let selected = self
+                    .selection_range()
+                    .and_then(|range| self.range_on_line(range, idx));
+                let block = self.block_on_line(idx);
+                for range in selected.clone().into_iter().chain(block) {
+                    overlays.push((range, select_style));
+                }
+                for range in self
+                    .extra_selections()
+                    .filter_map(|range| self.range_on_line(range, idx))
+                {
+                    overlays.push((range, select_style));
+                }
+                //The terminal only shows the main cursor, the others are drawn
+                let mut cursor_at_end = false;
+                for (y, x) in self.extra_cursors().filter(|(y, _)| *y == idx) {
+                    cursor_at_end |= x == content.len();
+                    let len = self.document.next_grapheme_len((y, x));
+                    overlays.push((x..x + len, cursor_style));
+                }
+                let fold = self.closed_fold_at(idx);
+                //Rows continuing a wrapped line are marked instead of numbered
+                let gutter_text = if shown.start == 0 {
+                    let marker = if fold.is_some() { '▸' } else { ' ' };
+                    format!("{idx:>gutter$}{marker}")
+                } else {
+                    format!("{:>gutter$} ", '↪')
+                };
+                let mut spans = vec![Span::styled(gutter_text, base)];
+                let shown = if self.wrap {
+                    shown
+                } else {
+                    //A wide grapheme cut by the left edge is shown as blank columns
+                    let mut start = graphemes::byte_at_col(&content, left, self.tab_width);
+                    let start_col = graphemes::display_col(&content, start, self.tab_width);
+                    if start_col < left {
+                        start = graphemes::next_grapheme(&content, start);
+                        let blank = graphemes::display_col(&content, start, self.tab_width) - left;
+                        spans.push(Span::styled(" ".repeat(blank), base));
+                    }
+                    start..content.len()
+                };
+                let ends_line = shown.end == content.len();
+                spans.extend(paint_line(&content, base, &overlays, shown, self.tab_width));
+                //Rows too short to reach the end of the block are filled so it looks square
+                let padding = self.block_padding(idx, &content, left);
+                if let Some((before, inside)) = padding.filter(|_| ends_line && !self.wrap) {
+                    spans.push(Span::styled(" ".repeat(before), base));
+                    spans.push(Span::styled(" ".repeat(inside), select_style));
+                }
+                if cursor_at_end && ends_line {
+                    spans.push(Span::styled(" ", cursor_style));
+                }
+                if let Some((start, end)) = fold.filter(|_| ends_line) {
+                    spans.push(Span::styled(
+                        format!(" ⋯ {} lines", end - start),
+                        fold_style,
+                    ));
+                }
+                //Lets empty lines inside the selection be seen
+                if content.is_empty() && !cursor_at_end && selected.is_some_and(|_| idx != self.y) {
+                    spans.push(Span::styled(" ", select_style));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        Paragraph::new(lines).render(self.area(), buf);
+        self.render_file_name(colors, buf);
+    }
+    ///Renders the name of the file on the last row of the area, marked if it is shown as hex or
+    ///if it is a large file
+    fn render_file_name(&self, colors: &Arc<HashMap<String, u32>>, buf: &mut Buffer) {
+        let fg = (**colors).get("text_fg").cloned().unwrap_or(0xffffff);
+        let mut file_name = match self.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => "Not a File".to_string(),
+        };
+        if self.hex.is_some() {
+            file_name.push_str(" [hex]");
+        }
+        match self.large.as_ref().map(|l| l.progress()) {
+            Some(Some(progress)) => file_name.push_str(&format!(" [large, indexing {progress}%]")),
+            Some(None) => file_name.push_str(" [large]"),
+            None => {}
+        }
+        let file_name = Line::from(file_name);
+        let len = file_name.width();
+        Paragraph::new(file_name.style(Style::default().fg(Color::from_u32(
+            (**colors).get("file_name_color").cloned().unwrap_or(fg),
+        ))))
+        .render(
+            Rect {
+                width: len as u16,
+                x: self.posx(),
+                y: self.h() + self.posy() - 1,
+                height: 1,
+            },
+            buf,
+        )
+    }
+}
+///Splits the given content into spans styled with the base style patched by every overlay that
+///covers them, later overlays being on top. Only the bytes inside `shown` are included, with tabs
+///drawn as spaces up to their tab stop
+fn paint_line(
+    content: &str,
+    base: Style,
+    overlays: &[(Range<usize>, Style)],
+    shown: Range<usize>,
+    tab_width: usize,
+) -> Vec<Span<'static>> {
+    let end = shown.end.min(content.len());
+    let start = shown.start.min(end);
+    let style_at = |idx: usize| {
+        overlays
+            .iter()
+            .filter(|(range, _)| range.contains(&idx))
+            .fold(base, |style, (_, overlay)| style.patch(*overlay))
+    };
+    //The style can only change where an overlay starts or ends
+    let mut bounds: Vec<usize> = overlays
+        .iter()
+        .flat_map(|(range, _)| [range.start, range.end])
+        .filter(|bound| start < *bound && *bound < end)
+        .collect();
+    bounds.sort_unstable();
+    let mut bounds = bounds.into_iter().peekable();
+    let mut spans = Vec::new();
+    let mut init = start;
+    let mut style = style_at(start);
+    let mut init_col = graphemes::display_col(content, start, tab_width);
+    let mut col = init_col;
+    //Split between graphemes only, so a style change never breaks one
+    for (idx, grapheme) in content[start..end]
+        .grapheme_indices(true)
+        .map(|(i, g)| (i + start, g))
+    {
+        let mut crossed = false;
+        while bounds.next_if(|bound| *bound <= idx).is_some() {
+            crossed = true;
+        }
+        let changed = crossed
+            .then(|| style_at(idx))
+            .filter(|current| *current != style);
+        if let Some(current) = changed {
+            let text = graphemes::expand_tabs(&content[init..idx], init_col, tab_width);
+            spans.push(Span::styled(text, style));
+            style = current;
+            init = idx;
+            init_col = col;
+        }
+        col += graphemes::grapheme_cols(grapheme, col, tab_width);
+    }
+    if init < end {
+        let text = graphemes::expand_tabs(&content[init..end], init_col, tab_width);
+        spans.push(Span::styled(text, style));
+    }
+    spans
+}
+impl std::fmt::Display for TextArea {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use isht::configuration::EditorSettings;
+    use ratatui::style::{Color, Modifier, Style};
+
+    use crate::helpers::{languages::LanguageRegistry, search::Replace};
+
+    use super::{paint_line, LineEnding, TextArea};
+
+    fn area() -> TextArea {
+        TextArea::new(
+            0,
+            0,
+            80,
+            24,
+            Arc::new(LanguageRegistry::new()),
+            Arc::new(EditorSettings::default()),
+        )
+    }
+
+    ///Gets an empty directory for the files of the given test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ishtar-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn missing_file_after_hex_starts_empty() {
+        let dir = scratch_dir("hex");
+        let binary = dir.join("data.bin");
+        std::fs::write(&binary, [0u8, 1, 2, 0xff, 0]).unwrap();
+        let mut area = area();
+        area.open_file(binary).unwrap();
+        assert!(area.hex.is_some());
+
+        let new = dir.join("new.txt");
+        area.open_file(new.clone()).unwrap();
+        assert!(area.hex.is_none());
+        assert_eq!(area.to_string(), "");
+        area.save(&dir).unwrap();
+        assert_eq!(std::fs::read(&new).unwrap(), b"");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_file_after_large_starts_empty() {
+        let dir = scratch_dir("large");
+        let large = dir.join("large.txt");
+        std::fs::write(&large, "line\n".repeat(300_000)).unwrap();
+        let settings = EditorSettings {
+            large_file_threshold: 1,
+            ..Default::default()
+        };
+        let mut area = TextArea::new(
+            0,
+            0,
+            80,
+            24,
+            Arc::new(LanguageRegistry::new()),
+            Arc::new(settings),
+        );
+        area.open_file(large).unwrap();
+        assert!(area.large.is_some());
+
+        let new = dir.join("new.txt");
+        area.open_file(new.clone()).unwrap();
+        assert!(area.large.is_none());
+        assert_eq!(area.to_string(), "");
+        area.save(&dir).unwrap();
+        assert_eq!(std::fs::read(&new).unwrap(), b"");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn toggling_hex_keeps_the_history() {
+        let mut area = area();
+        area.set_content("abc".to_string());
+        area.insert_at((0, 3), "d");
+        area.toggle_hex().unwrap();
+        area.toggle_hex().unwrap();
+        assert_eq!(area.to_string(), "abcd");
+        area.undo();
+        assert_eq!(area.to_string(), "abc");
+    }
+
+    #[test]
+    fn hex_edits_are_undone_at_once() {
+        let mut area = area();
+        area.set_content("abc".to_string());
+        area.insert_at((0, 3), "d");
+        area.toggle_hex().unwrap();
+        let hex = area.hex.as_mut().unwrap();
+        assert!(hex.write_nibble('6') && hex.write_nibble('2'));
+        area.toggle_hex().unwrap();
+        assert_eq!(area.to_string(), "bbcd");
+        area.undo();
+        assert_eq!(area.to_string(), "abcd");
+        area.undo();
+        assert_eq!(area.to_string(), "abc");
+        area.redo();
+        area.redo();
+        assert_eq!(area.to_string(), "bbcd");
+    }
+
+    #[test]
+    fn toggling_mixed_line_endings_drops_the_kept_crs() {
+        let mut area = area();
+        area.set_content("a\r\nb\nc\r\n".to_string());
+        assert!(area.format().mixed_endings);
+        assert_eq!(area.toggle_line_ending(), LineEnding::Lf);
+        assert_eq!(area.to_string(), "a\nb\nc");
+        assert!(!area.format().mixed_endings);
+        area.undo();
+        assert_eq!(area.to_string(), "a\r\nb\nc\r");
+    }
+
+    #[test]
+    fn editing_crlf_lines_of_mixed_files_keeps_their_cr() {
+        let mut area = area();
+        area.set_content("ab\r\nc\n".to_string());
+        area.enter_writing();
+        area.goto_end_of_line();
+        assert_eq!(area.x(), 2);
+        area.move_right();
+        assert_eq!(area.x(), 2);
+        area.write_char('X');
+        area.newline();
+        area.write_char('Y');
+        assert_eq!(area.to_string(), "abX\r\nY\r\nc");
+        area.move_down();
+        area.goto_end_of_line();
+        area.move_up();
+        assert_eq!((area.y(), area.x()), (1, 1));
+        area.del();
+        assert_eq!(area.to_string(), "abX\r\nYc");
+    }
+
+    #[test]
+    fn painting_splits_runs_where_overlays_change_between_graphemes() {
+        let red = Style::default().fg(Color::Red);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        //the last overlay starts inside the accented e, which keeps the style of its first byte
+        let overlays = [(1..4, red), (3..6, bold), (6..8, red)];
+        let content = "ab\tcde\u{301}f";
+        let spans = paint_line(content, Style::default(), &overlays, 0..content.len(), 4);
+        let painted: Vec<(&str, Style)> = spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        assert_eq!(
+            painted,
+            [
+                ("a", Style::default()),
+                ("b  ", red),
+                ("c", red.patch(bold)),
+                ("de\u{301}", bold),
+                ("f", Style::default()),
+            ]
+        );
+        let spans = paint_line(content, Style::default(), &overlays, 4..5, 4);
+        assert_eq!(spans.len(), 1);
+        assert_eq!((spans[0].content.as_ref(), spans[0].style), ("d", bold));
+    }
+
+    #[test]
+    fn bracket_pairs_are_scanned_only_through_the_given_lines() {
+        let mut area = area();
+        area.set_content(format!("({})", "\nx".repeat(10)));
+        area.set_cursor((0, 0));
+        assert_eq!(area.bracket_pair(0..11), Some(((0, 0), (10, 1))));
+        assert_eq!(area.bracket_pair(0..10), None);
+        area.set_cursor((10, 1));
+        assert_eq!(area.bracket_pair(0..11), Some(((10, 1), (0, 0))));
+        assert_eq!(area.bracket_pair(1..11), None);
+    }
+
+    #[test]
+    fn replacing_patterns_that_match_empty_text_ends() {
+        let mut area = area();
+        area.set_content("axxb\nbx\nc".to_string());
+        let replace = Replace::parse("%s/x*/-/g").unwrap();
+        assert_eq!(area.replace_all(&replace), 2);
+        assert_eq!(area.to_string(), "a-b\nb-\nc");
+        area.undo();
+        assert_eq!(area.to_string(), "axxb\nbx\nc");
+    }
+
+    #[test]
+    fn replacing_the_first_match_of_each_line() {
+        let mut area = area();
+        area.set_content("aa\naa".to_string());
+        let replace = Replace::parse("%s/a/b\\n/").unwrap();
+        assert_eq!(area.replace_all(&replace), 2);
+        assert_eq!(area.to_string(), "b\na\nb\na");
+    }
+
+    #[test]
+    fn next_occurrences_wrap_and_skip_the_selected_ones() {
+        let mut area = area();
+        area.set_content("foo bar foo\nfoo".to_string());
+        area.set_cursor((0, 9));
+        area.enter_selection();
+        assert!(area.select_next_occurrence());
+        assert_eq!(area.selection_range(), Some(((0, 8), (0, 11))));
+        assert!(area.select_next_occurrence());
+        assert_eq!(area.selection_range(), Some(((1, 0), (1, 3))));
+        assert!(area.select_next_occurrence());
+        assert_eq!(area.selection_range(), Some(((0, 0), (0, 3))));
+        assert_eq!(area.cursor_count(), 3);
+        assert!(!area.select_next_occurrence());
+        assert_eq!(area.cursor_count(), 3);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::helpers::{history::BufferPos, search::Replace};
+
+use super::TextArea;
+
+///What to do with the match a replace is asking about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaceAnswer {
+    Yes,
+    No,
+    All,
+    Quit,
+}
+
+impl ReplaceAnswer {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'y' => Some(Self::Yes),
+            'n' => Some(Self::No),
+            'a' => Some(Self::All),
+            'q' => Some(Self::Quit),
+            _ => None,
+        }
+    }
+}
+
+///A replace waiting for an answer about its current match
+#[derive(Debug)]
+pub struct PendingReplace {
+    replace: Replace,
+    current: (BufferPos, BufferPos),
+    end_back: usize, //bytes between the end of the range and the end of the document
+    count: usize,
+}
+
+impl TextArea {
+    ///Gets where the given replace starts and how many bytes are after its end. The amount of
+    ///bytes after the end does not change while replacing, unlike the end position
+    fn replace_bounds(&self, replace: &Replace) -> (BufferPos, usize) {
+        let (start, end) = if replace.whole_buffer {
+            let last = self.document.len_lines() - 1;
+            ((0, 0), (last, self.document.line_len(last)))
+        } else {
+            self.selection_range()
+                .unwrap_or(((self.y, 0), (self.y, self.document.line_len(self.y))))
+        };
+        (
+            start,
+            self.document.len_bytes() - self.document.pos_to_byte(end),
+        )
+    }
+    ///Finds the first match of the replace starting at the given position that finishes before
+    ///the end of the range
+    fn next_replace_match(
+        &self,
+        replace: &Replace,
+        from: BufferPos,
+        end_back: usize,
+    ) -> Option<(BufferPos, BufferPos)> {
+        let end = self
+            .document
+            .byte_to_pos(self.document.len_bytes() - end_back);
+        (from.0..=end.0).find_map(|y| {
+            let line = self.document.line_str(y);
+            replace
+                .search
+                .find_all(&line)
+                .into_iter()
+                .find(|m| (y > from.0 || m.start >= from.1) && (y < end.0 || m.end <= end.1))
+                .map(|m| ((y, m.start), (y, m.end)))
+        })
+    }
+    ///Replaces the given match and returns where the search for the next one must start
+    fn replace_match(
+        &mut self,
+        replace: &Replace,
+        (start, end): (BufferPos, BufferPos),
+    ) -> BufferPos {
+        let line = self.document.line_string(start.0);
+        let text = replace
+            .expand(&line, start.1)
+            .map(|(_, text)| text)
+            .unwrap_or_default();
+        self.remove_between(start, end);
+        let end = self.insert_at(start, &text);
+        self.set_cursor(start);
+        self.after_match(replace, end)
+    }
+    ///Gets where the search for the next match must start after a match that finished at the
+    ///given position. Without the global flag only the first match of each line is taken
+    fn after_match(&self, replace: &Replace, end: BufferPos) -> BufferPos {
+        if replace.global {
+            end
+        } else {
+            (end.0 + 1, 0)
+        }
+    }
+    ///Replaces every match starting at the given position. Returns how many were replaced
+    fn replace_from(&mut self, replace: &Replace, mut from: BufferPos, end_back: usize) -> usize {
+        let mut count = 0;
+        while let Some(found) = self.next_replace_match(replace, from, end_back) {
+            from = self.replace_match(replace, found);
+            count += 1;
+        }
+        count
+    }
+    ///Replaces every match inside the whole buffer or the selection, as a single undo unit.
+    ///Returns how many were replaced
+    pub fn replace_all(&mut self, replace: &Replace) -> usize {
+        let (from, end_back) = self.replace_bounds(replace);
+        self.enter_writing();
+        self.history.begin_group();
+        let count = self.replace_from(replace, from, end_back);
+        self.history.end_group();
+        count
+    }
+    ///Starts a replace that asks before each replacement, moving the cursor to its first match.
+    ///Returns false if there is nothing to replace
+    pub fn begin_replace(&mut self, replace: Replace) -> bool {
+        let (from, end_back) = self.replace_bounds(&replace);
+        self.enter_writing();
+        let Some(current) = self.next_replace_match(&replace, from, end_back) else {
+            return false;
+        };
+        self.history.begin_group();
+        self.set_cursor(current.0);
+        self.search = Some(replace.search.clone());
+        self.replacing = Some(PendingReplace {
+            replace,
+            current,
+            end_back,
+            count: 0,
+        });
+        true
+    }
+    ///Handles the answer about the current match of the pending replace. Returns how many
+    ///matches were replaced once the replace finishes
+    pub fn answer_replace(&mut self, answer: ReplaceAnswer) -> Option<usize> {
+        let mut pending = self.replacing.take()?;
+        let from = match answer {
+            ReplaceAnswer::Yes => {
+                pending.count += 1;
+                self.replace_match(&pending.replace, pending.current)
+            }
+            ReplaceAnswer::No => self.after_match(&pending.replace, pending.current.1),
+            ReplaceAnswer::All => {
+                let (start, _) = pending.current;
+                pending.count += self.replace_from(&pending.replace, start, pending.end_back);
+                return Some(self.finish_replace(pending));
+            }
+            ReplaceAnswer::Quit => return Some(self.finish_replace(pending)),
+        };
+        match self.next_replace_match(&pending.replace, from, pending.end_back) {
+            Some(next) => {
+                self.set_cursor(next.0);
+                pending.current = next;
+                self.replacing = Some(pending);
+                None
+            }
+            None => Some(self.finish_replace(pending)),
+        }
+    }
+    fn finish_replace(&mut self, pending: PendingReplace) -> usize {
+        self.history.end_group();
+        self.search = None;
+        pending.count
+    }
+    pub fn is_replacing(&self) -> bool {
+        self.replacing.is_some()
+    }
+}
@@ -0,0 +1,130 @@
+use std::ops::Range;
+
+use crate::helpers::{history::BufferPos, search::Search};
+
+use super::TextArea;
+
+impl TextArea {
+    ///Sets the query being searched and jumps to its first match after the position where the
+    ///search started, so the cursor follows the query while it's being typed. An empty query
+    ///removes the search
+    pub fn search(&mut self, query: &str) -> Result<(), regex::Error> {
+        if let Some(hex) = &mut self.hex {
+            hex.search(query);
+            return Ok(());
+        }
+        if let Some(large) = &mut self.large {
+            return large.search(query);
+        }
+        let origin = *self.search_origin.get_or_insert((self.y, self.x));
+        self.set_cursor(origin);
+        if query.is_empty() {
+            self.search = None;
+            return Ok(());
+        }
+        let search = Search::new(query);
+        self.search = search.as_ref().ok().cloned();
+        if let Some((start, _)) = self.find_match(origin, true, true) {
+            self.set_cursor(start);
+        }
+        search.map(|_| ())
+    }
+    ///Removes the current search. If it was still being typed, the cursor goes back to where it
+    ///started
+    pub fn clear_search(&mut self) {
+        if let Some(hex) = &mut self.hex {
+            hex.clear_search();
+        }
+        if let Some(large) = &mut self.large {
+            large.clear_search();
+        }
+        if let Some(origin) = self.search_origin.take() {
+            self.set_cursor(origin);
+        }
+        self.search = None;
+    }
+    ///Stops following the query being typed, keeping the cursor where it is
+    pub fn confirm_search(&mut self) {
+        if let Some(hex) = &mut self.hex {
+            hex.confirm_search();
+        }
+        if let Some(large) = &mut self.large {
+            large.confirm_search();
+        }
+        self.search_origin = None;
+    }
+    pub fn current_search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+    ///Moves the cursor to the next match of the current search, backwards if not forward. Wraps
+    ///around the buffer. Returns if a match was found
+    pub fn search_next(&mut self, forward: bool) -> bool {
+        if let Some(hex) = &mut self.hex {
+            return hex.search_next(forward);
+        }
+        if let Some(large) = &mut self.large {
+            return large.search_next(forward);
+        }
+        self.confirm_search();
+        match self.find_match((self.y, self.x), forward, false) {
+            Some((start, _)) => {
+                self.set_cursor(start);
+                true
+            }
+            None => false,
+        }
+    }
+    ///Gets the matches of the current search on the line at the given index
+    pub fn matches_on_line(&self, idx: usize) -> Vec<Range<usize>> {
+        match (&self.search, self.content(idx)) {
+            (Some(search), Some(line)) => search.find_all(&line.to_string()),
+            _ => Vec::new(),
+        }
+    }
+    ///Finds the first match starting from the given position going through the whole buffer and
+    ///wrapping around it. If inclusive, a match starting exactly at the position is accepted
+    pub fn find_match(
+        &self,
+        from: BufferPos,
+        forward: bool,
+        inclusive: bool,
+    ) -> Option<(BufferPos, BufferPos)> {
+        let search = self.search.as_ref()?;
+        let len = self.document.len_lines();
+        //the line of the position is visited twice, the second time for the part skipped before
+        for step in 0..=len {
+            let y = if forward {
+                (from.0 + step) % len
+            } else {
+                (from.0 + len - step % len) % len
+            };
+            let matches = search.find_all(&self.document.line_str(y));
+            let accepts = |m: &Range<usize>| {
+                if step == 0 {
+                    if forward {
+                        m.start > from.1 || (inclusive && m.start == from.1)
+                    } else {
+                        m.start < from.1
+                    }
+                } else if step == len {
+                    if forward {
+                        m.start <= from.1
+                    } else {
+                        m.start >= from.1
+                    }
+                } else {
+                    true
+                }
+            };
+            let found = if forward {
+                matches.into_iter().find(accepts)
+            } else {
+                matches.into_iter().rfind(accepts)
+            };
+            if let Some(m) = found {
+                return Some(((y, m.start), (y, m.end)));
+            }
+        }
+        None
+    }
+}
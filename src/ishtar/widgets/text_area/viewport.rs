@@ -0,0 +1,89 @@
+use super::TextArea;
+
+impl TextArea {
+    ///Gets how many rows of the buffer are shown. The last row of the area shows the file name
+    #[inline]
+    pub(super) fn text_rows(&self) -> usize {
+        (self.h() as usize).saturating_sub(1).max(1)
+    }
+    ///Gets how many columns of the buffer are shown, without the line numbers
+    #[inline]
+    pub(super) fn text_cols(&self) -> usize {
+        (self.w() as usize).saturating_sub(self.xoffset()).max(1)
+    }
+    ///Gets the scrolloff margins as (rows, columns), shrunk to fit the area
+    fn margins(&self) -> (usize, usize) {
+        (
+            self.settings.scrolloff.min((self.text_rows() - 1) / 2),
+            self.settings.side_scrolloff.min((self.text_cols() - 1) / 2),
+        )
+    }
+    ///Moves the view the least needed to have the cursor inside it, away from the edges by the
+    ///scrolloff margins. There is no margin below the last line of the buffer. Wrapped lines are
+    ///never scrolled horizontally
+    fn scroll_to_cursor(&self) {
+        let (margin, side_margin) = self.margins();
+        let row = self.cursor_row();
+        let below = margin.min(self.last_screen_row() - row);
+        let top = self
+            .top
+            .get()
+            .min(row.saturating_sub(margin))
+            .max((row + below + 1).saturating_sub(self.text_rows()));
+        self.top.set(top);
+        if self.wrap {
+            self.left.set(0);
+            return;
+        }
+        let col = self.cursor_row_col();
+        let left = self
+            .left
+            .get()
+            .min(col.saturating_sub(side_margin))
+            .max((col + side_margin + 1).saturating_sub(self.text_cols()));
+        self.left.set(left);
+    }
+    ///Gets the row shown at the top of the area
+    pub fn top_row(&self) -> usize {
+        self.scroll_to_cursor();
+        self.top.get()
+    }
+    ///Gets the char column shown at the left of the area
+    pub fn left_col(&self) -> usize {
+        self.scroll_to_cursor();
+        self.left.get()
+    }
+    ///Scrolls the view by the given rows and columns. The cursor stays where it is unless it would
+    ///be left outside the view or the scrolloff margins, going to the closest place inside them
+    pub fn scroll(&mut self, rows: isize, cols: isize) {
+        self.scroll_to_cursor();
+        let (margin, side_margin) = self.margins();
+        let last = self.last_screen_row();
+        let top = self
+            .top
+            .get()
+            .saturating_add_signed(rows)
+            .min(last.saturating_sub(margin));
+        let left = if self.wrap {
+            0
+        } else {
+            self.left.get().saturating_add_signed(cols)
+        };
+        self.top.set(top);
+        self.left.set(left);
+        let bottom = top + self.text_rows() - 1;
+        let row = self
+            .cursor_row()
+            .max(if top == 0 { 0 } else { top + margin })
+            .min(if last <= bottom {
+                last
+            } else {
+                bottom - margin
+            });
+        let col = self
+            .cursor_row_col()
+            .max(if left == 0 { 0 } else { left + side_margin })
+            .min(left + self.text_cols() - 1 - side_margin);
+        self.set_cursor_row(row, col);
+    }
+}
@@ -0,0 +1,208 @@
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::helpers::graphemes;
+
+use super::TextArea;
+
+impl TextArea {
+    ///Uses the editing settings of the filetype of the buffer, found by its language or
+    ///its file extension, falling back to the general ones
+    pub(super) fn apply_filetype_settings(&mut self) {
+        let extension = self.file_extension().and_then(|e| e.to_str());
+        let filetype = [self.language().map(|l| l.name), extension]
+            .into_iter()
+            .flatten()
+            .find_map(|name| self.settings.filetypes.get(name));
+        self.wrap = filetype.and_then(|f| f.wrap).unwrap_or(self.settings.wrap);
+        self.wrap_column = filetype
+            .and_then(|f| f.wrap_column)
+            .unwrap_or(self.settings.wrap_column);
+        self.indent_width = filetype
+            .and_then(|f| f.indent_width)
+            .unwrap_or(self.settings.indent_width);
+        self.expandtab = filetype
+            .and_then(|f| f.expandtab)
+            .unwrap_or(self.settings.expandtab);
+        self.tab_width = filetype
+            .and_then(|f| f.tab_width)
+            .unwrap_or(self.settings.tab_width);
+        self.auto_pairs = filetype
+            .and_then(|f| f.auto_pairs)
+            .unwrap_or(self.settings.auto_pairs);
+    }
+    ///Starts or stops soft wrapping the lines of this buffer
+    pub fn toggle_wrap(&mut self) {
+        self.wrap = !self.wrap;
+        self.left.set(0);
+    }
+    #[inline]
+    pub fn is_wrapping(&self) -> bool {
+        self.wrap
+    }
+    ///Gets how many columns fit in a wrapped row. The last column of the area is left for the
+    ///cursor at the end of a full row
+    fn wrap_width(&self) -> usize {
+        let cols = self.text_cols().saturating_sub(1).max(1);
+        match self.wrap_column {
+            0 => cols,
+            column => column.min(cols),
+        }
+    }
+    ///Splits the line at the given index into the byte ranges shown on each row. Without soft
+    ///wrap the whole line is a single row. Rows are broken after the last whitespace that fits in
+    ///them, or inside the word when it's longer than a row
+    pub fn line_segments(&self, idx: usize) -> Vec<Range<usize>> {
+        let line = self.document.line_str(idx);
+        let whole = 0..line.len();
+        if !self.wrap {
+            return vec![whole];
+        }
+        let width = self.wrap_width();
+        let mut segments = Vec::new();
+        let mut start = 0;
+        let mut cols = 0; //columns taken on the current row
+        let mut line_col = 0; //column of the grapheme from the init of the line, for tab stops
+        let mut breakpoint = None; //byte after the last whitespace on the current row
+        for (i, grapheme) in line.grapheme_indices(true) {
+            let grapheme_cols = graphemes::grapheme_cols(grapheme, line_col, self.tab_width);
+            if cols + grapheme_cols > width && i > start {
+                let end = breakpoint.filter(|b| *b > start).unwrap_or(i);
+                segments.push(start..end);
+                cols = line_col - graphemes::display_col(&line, end, self.tab_width);
+                start = end;
+                breakpoint = None;
+            }
+            cols += grapheme_cols;
+            line_col += grapheme_cols;
+            if grapheme.chars().all(char::is_whitespace) {
+                breakpoint = Some(i + grapheme.len());
+            }
+        }
+        segments.push(start..line.len());
+        segments
+    }
+    ///Gets the index of the segment the given byte is shown on
+    #[inline]
+    fn segment_of(segments: &[Range<usize>], x: usize) -> usize {
+        segments.iter().rposition(|s| s.start <= x).unwrap_or(0)
+    }
+    ///Gets how many rows the line at the given index takes
+    #[inline]
+    fn line_rows(&self, idx: usize) -> usize {
+        if self.wrap {
+            self.line_segments(idx).len()
+        } else {
+            1
+        }
+    }
+    ///Gets the row of the area where the given line starts, counting from the first line of the
+    ///buffer. Closed folds take a single line and wrapped lines take one row per segment
+    pub fn screen_row(&self, idx: usize) -> usize {
+        if !self.wrap {
+            return self.visual_row(idx);
+        }
+        let idx = self.visible_line(idx);
+        let mut row = 0;
+        let mut line = Some(0);
+        while let Some(current) = line.filter(|l| *l < idx) {
+            row += self.line_rows(current);
+            line = self.next_visible_line(current);
+        }
+        row
+    }
+    ///Gets the line shown at the given row, counting from the first line of the buffer, with the
+    ///index of the segment shown there
+    pub fn line_at_screen_row(&self, row: usize) -> (usize, usize) {
+        if !self.wrap {
+            return (self.line_at_row(row), 0);
+        }
+        let mut first = 0; //row where the current line starts
+        let mut line = 0;
+        loop {
+            let rows = self.line_rows(line);
+            match self.next_visible_line(line) {
+                Some(next) if first + rows <= row => {
+                    first += rows;
+                    line = next;
+                }
+                _ => return (line, (row - first).min(rows - 1)),
+            }
+        }
+    }
+    ///Gets the row of the cursor, counting from the first line of the buffer
+    pub(super) fn cursor_row(&self) -> usize {
+        if !self.wrap {
+            return self.visual_row(self.y);
+        }
+        let segments = self.line_segments(self.y);
+        self.screen_row(self.y) + Self::segment_of(&segments, self.x)
+    }
+    ///Gets the display column of the cursor inside the row it is shown on
+    pub(super) fn cursor_row_col(&self) -> usize {
+        let col = self.document.display_col((self.y, self.x), self.tab_width);
+        if !self.wrap {
+            return col;
+        }
+        let segments = self.line_segments(self.y);
+        let start = segments[Self::segment_of(&segments, self.x)].start;
+        col - self.document.display_col((self.y, start), self.tab_width)
+    }
+    ///Gets the row of the last line of the buffer
+    pub(super) fn last_screen_row(&self) -> usize {
+        let last = self.document.len_lines() - 1;
+        self.screen_row(last) + self.line_rows(self.visible_line(last)) - 1
+    }
+    ///Places the cursor at the given row, counting from the first line of the buffer, as close as
+    ///possible to the given display column of the row
+    pub(super) fn set_cursor_row(&mut self, row: usize, col: usize) {
+        let (line, segment) = self.line_at_screen_row(row);
+        let segments = self.line_segments(line);
+        let shown = &segments[segment];
+        let content = self.document.line_str(line);
+        let start = graphemes::display_col(&content, shown.start, self.tab_width);
+        let x = graphemes::byte_at_col(&content, start + col, self.tab_width);
+        //the end of a row that is not the last one is shown on the next row
+        let last = if segment + 1 < segments.len() {
+            graphemes::prev_grapheme(&content, shown.end).max(shown.start)
+        } else {
+            shown.end
+        };
+        self.y = line;
+        self.x = x.min(last).min(self.line_end(line));
+    }
+    ///Moves the cursor by the given amount of rows when wrapping, keeping its column on the row.
+    ///Returns false if there is no row there
+    pub(super) fn move_rows(&mut self, rows: isize) -> bool {
+        let Some(row) = self.cursor_row().checked_add_signed(rows) else {
+            return false;
+        };
+        if row > self.last_screen_row() {
+            return false;
+        }
+        self.set_cursor_row(row, self.cursor_row_col());
+        true
+    }
+    ///Gets the rows shown in the area, as the line shown on each one and the bytes of it that are
+    ///shown
+    pub fn visible_rows(&self) -> Vec<(usize, Range<usize>)> {
+        let h = (self.h() - 1) as usize; //-1 due to the last be reserved to the file name
+        let mut rows = Vec::with_capacity(h);
+        let (mut line, mut segment) = self.line_at_screen_row(self.top_row());
+        loop {
+            let segments = self.line_segments(line);
+            for shown in segments.into_iter().skip(segment) {
+                if rows.len() == h {
+                    return rows;
+                }
+                rows.push((line, shown));
+            }
+            segment = 0;
+            match self.next_visible_line(line) {
+                Some(next) => line = next,
+                None => return rows,
+            }
+        }
+    }
+}
@@ -4,10 +4,10 @@ use std::{
     sync::Arc,
 };
 
-use isht::CmdTask;
-use ratatui::{buffer::Buffer, crossterm::event::KeyCode, prelude::Rect, widgets::Widget, Frame};
+use isht::{configuration::EditorSettings, CmdTask};
+use ratatui::{crossterm::event::KeyCode, prelude::Rect, widgets::Widget, Frame};
 
-use crate::helpers::AreaOrder;
+use crate::helpers::{languages::LanguageRegistry, AreaOrder};
 
 use super::{text_area::TextArea, IshtarSelectable};
 #[derive(Debug)]
@@ -17,45 +17,59 @@ pub struct WriteableArea {
     focused_writer: usize,
     area: (u16, u16),
     colors: Arc<HashMap<String, u32>>,
+    languages: Arc<LanguageRegistry>,
+    settings: Arc<EditorSettings>,
 }
 
 impl WriteableArea {
-    pub fn new_horizontal(w: u16, h: u16, colors: Arc<HashMap<String, u32>>) -> Self {
+    pub fn new_horizontal(
+        w: u16,
+        h: u16,
+        colors: Arc<HashMap<String, u32>>,
+        languages: Arc<LanguageRegistry>,
+        settings: Arc<EditorSettings>,
+    ) -> Self {
         let mut s = Self {
             writers: Vec::new(),
             order: AreaOrder::Horizontal,
             focused_writer: 0,
             area: (w, h),
             colors,
+            languages,
+            settings,
         };
         s.create_area();
         s
     }
-    pub fn new_vertical(w: u16, h: u16, colors: Arc<HashMap<String, u32>>) -> Self {
+    pub fn new_vertical(
+        w: u16,
+        h: u16,
+        colors: Arc<HashMap<String, u32>>,
+        languages: Arc<LanguageRegistry>,
+        settings: Arc<EditorSettings>,
+    ) -> Self {
         let mut s = Self {
             writers: Vec::new(),
             order: AreaOrder::Vertical,
             focused_writer: 0,
             area: (w, h),
             colors,
+            languages,
+            settings,
         };
         s.create_area();
         s
     }
-    ///Gets the cursor position based on the active text area
+    pub fn settings(&self) -> &EditorSettings {
+        &self.settings
+    }
+    ///Gets the cursor position based on the active text area and the part of its buffer it shows
     pub fn cursor(&self) -> (usize, usize) {
         let current_writer = &self.writers[self.focused_writer];
-        let w = if let AreaOrder::Horizontal = self.order {
-            self.area.0 as usize / self.len() * self.focused_writer + current_writer.cursor_x()
-        } else {
-            self.cursor_x()
-        };
-        let y = if let AreaOrder::Vertical = self.order {
-            self.area.1 as usize / self.len() * self.focused_writer + current_writer.cursor_y()
-        } else {
-            self.cursor_y()
-        };
-        (w, y)
+        (
+            current_writer.posx() as usize + current_writer.cursor_x(),
+            current_writer.posy() as usize + current_writer.cursor_y(),
+        )
     }
     ///Gets how many TexArea this Writeable is handling
     pub fn len(&self) -> usize {
@@ -127,7 +141,14 @@ impl WriteableArea {
     }
     ///Creates a new text area and modifies the view to handle the new one
     pub fn create_area(&mut self) {
-        self.writers.push(TextArea::new(0, 0, 0, 2));
+        self.writers.push(TextArea::new(
+            0,
+            0,
+            0,
+            2,
+            self.languages.clone(),
+            self.settings.clone(),
+        ));
         self.modify_areas();
     }
     ///Deletes the current active area
@@ -176,18 +197,36 @@ impl IshtarSelectable for WriteableArea {
         1
     }
     fn keydown(&mut self, key: ratatui::crossterm::event::KeyCode) -> isht::CmdTask {
+        if key != KeyCode::Esc
+            && (self.current_area_mut().hex_keydown(key)
+                || self.current_area_mut().large_keydown(key))
+        {
+            return CmdTask::Null;
+        }
         match key {
             KeyCode::Esc => return CmdTask::EnterNormal,
+            //Typing over a block replaces every row of it
+            KeyCode::Char(c) if self.is_block_selecting() => {
+                return self.current_area_mut().write_into_block(c)
+            }
+            KeyCode::Backspace | KeyCode::Delete if self.is_block_selecting() => {
+                self.current_area_mut().delete_selection();
+                return CmdTask::EnterModify;
+            }
             KeyCode::Char(c) => self.write_char(c),
+            //Only existing bytes are overwritten on the hex view
+            _ if self.is_hex() => {}
             KeyCode::Delete => self.current_area_mut().del(),
             KeyCode::Backspace => self.current_area_mut().backspace(),
             KeyCode::Enter => self.current_area_mut().newline(),
-            KeyCode::Up => self.move_up(),
-            KeyCode::Down => self.move_down(),
-            KeyCode::Left => self.move_left(),
-            KeyCode::Right => self.move_right(),
-            KeyCode::End => self.goto_end_of_line(),
-            KeyCode::Home => self.goto_init_of_line(),
+            KeyCode::Tab => self.current_area_mut().tab(),
+            KeyCode::BackTab => self.current_area_mut().backtab(),
+            KeyCode::Up => self.at_every_cursor(TextArea::move_up),
+            KeyCode::Down => self.at_every_cursor(TextArea::move_down),
+            KeyCode::Left => self.at_every_cursor(TextArea::move_left),
+            KeyCode::Right => self.at_every_cursor(TextArea::move_right),
+            KeyCode::End => self.at_every_cursor(TextArea::goto_end_of_line),
+            KeyCode::Home => self.at_every_cursor(TextArea::goto_init_of_line),
             _ => {}
         }
         CmdTask::Null
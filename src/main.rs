@@ -4,4 +4,4 @@ mod ishtar;
 fn main() {
     let mut ishtar = Ishtar::new();
     ishtar.run().unwrap();
-}
\ No newline at end of file
+}